@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use crate::config::{validate_config, BangDetails, BangMode, Config, ConfigError};
+use crate::interpolate::{has_references, InterpolationError};
+use crate::models::AdminBangForm;
+use crate::resolver::{Action, BangResolver};
+
+// Bangs changed through the admin API, kept apart from config.yml so the
+// hand-written file and its comments are never rewritten
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BangOverlay {
+    pub bangs: BTreeMap<String, BangDetails>,
+    pub private_bangs: BTreeMap<String, BangDetails>,
+    // Bangs from config.yml that were deleted or renamed
+    pub removed: BTreeSet<String>,
+}
+
+// A bang as seen by the admin API, as written so secrets filled in from
+// ${...} are never shown or saved
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminBang {
+    pub name: String,
+    pub private: bool,
+    pub bang: BangDetails,
+}
+
+#[derive(Debug)]
+pub enum AdminError {
+    NotFound(String),
+    AlreadyExists(String),
+    Invalid(ConfigError),
+    Io(std::io::Error),
+    Yaml(serde_yaml::Error),
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdminError::NotFound(name) => write!(f, "Bang '{}' not found", name),
+            AdminError::AlreadyExists(name) => write!(f, "Bang '{}' already exists", name),
+            AdminError::Invalid(e) => write!(f, "{}", e),
+            AdminError::Io(e) => write!(f, "Overlay file error: {}", e),
+            AdminError::Yaml(e) => write!(f, "Overlay file error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {}
+
+impl From<ConfigError> for AdminError {
+    fn from(e: ConfigError) -> Self {
+        AdminError::Invalid(e)
+    }
+}
+
+impl From<std::io::Error> for AdminError {
+    fn from(e: std::io::Error) -> Self {
+        AdminError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for AdminError {
+    fn from(e: serde_yaml::Error) -> Self {
+        AdminError::Yaml(e)
+    }
+}
+
+// Look up a public or private bang by name
+pub fn find_bang(config: &Config, name: &str) -> Option<AdminBang> {
+    let (bang, private) = match config.bangs.get(name) {
+        Some(bang) => (bang, false),
+        None => (config.private_bangs.get(name)?, true),
+    };
+    Some(AdminBang {
+        name: name.to_string(),
+        private,
+        bang: bang.as_written(),
+    })
+}
+
+// All public and private bangs, sorted by name
+pub fn list_bangs(config: &Config) -> Vec<AdminBang> {
+    let mut bangs: Vec<AdminBang> = config
+        .bangs
+        .keys()
+        .chain(config.private_bangs.keys())
+        .filter_map(|name| find_bang(config, name))
+        .collect();
+    bangs.sort_by(|a, b| a.name.cmp(&b.name));
+    bangs
+}
+
+// Where a bang would send a search, without following it. Err explains why
+// the search would be refused, so admins can test a bang before saving it.
+pub fn preview_target(config: &Config, bang: &BangDetails, term: &str) -> Result<String, String> {
+    let filled = interpolate_bang(config, "", bang).map_err(|e| match e {
+        ConfigError::Interpolation { error, .. } => error.to_string(),
+        e => e.to_string(),
+    })?;
+    let resolver = BangResolver::new(config);
+    // Shown as written, so values filled in from ${...} stay hidden
+    let written = || resolver.bang_target(&bang.as_written(), term);
+    match resolver.resolve_bang(&filled, term) {
+        Action::Redirect(_) | Action::Post(_) => written(),
+        Action::Blocked { violation, .. } if filled.written.is_some() => {
+            Err(violation.as_written(&written()?).to_string())
+        }
+        Action::Blocked { violation, .. } => Err(violation.to_string()),
+        Action::Invalid(message) => Err(message),
+        Action::Bookmark => Err("bookmark commands have no preview".to_string()),
+    }
+}
+
+// Fill the admin page's edit form from an existing bang
+pub fn form_from_bang(bang: &AdminBang, sample: &str) -> AdminBangForm {
+    AdminBangForm {
+        original: bang.name.clone(),
+        key: bang.name.clone(),
+        name: bang.bang.name.clone(),
+        url: bang.bang.url.clone(),
+        icon: bang.bang.icon.clone(),
+        home_url: bang.bang.home_url.clone().unwrap_or_default(),
+        mode: match bang.bang.mode {
+            BangMode::Search => "search",
+            BangMode::Navigate => "navigate",
+        }
+        .to_string(),
+        private: bang.private.then(|| "on".to_string()),
+        sample: sample.to_string(),
+        action: String::new(),
+    }
+}
+
+// Apply the edit form to a bang's details. Settings the form does not show,
+// such as the policy or POST fields, are kept from the existing bang.
+pub fn bang_from_form(form: &AdminBangForm, existing: BangDetails) -> BangDetails {
+    let home_url = form.home_url.trim();
+    BangDetails {
+        url: form.url.trim().to_string(),
+        name: form.name.trim().to_string(),
+        icon: form.icon.trim().to_string(),
+        mode: if form.mode == "navigate" { BangMode::Navigate } else { BangMode::Search },
+        home_url: (!home_url.is_empty()).then(|| home_url.to_string()),
+        ..existing
+    }
+}
+
+// Values the config files filled in from ${...}, by their text as written
+fn interpolated_values(config: &Config) -> HashMap<String, String> {
+    fn collect(written: &Value, filled: &Value, values: &mut HashMap<String, String>) {
+        match (written, filled) {
+            (Value::String(written), Value::String(filled)) if written.contains("${") => {
+                values.insert(written.clone(), filled.clone());
+            }
+            (Value::Sequence(written), Value::Sequence(filled)) => {
+                for (written, filled) in written.iter().zip(filled) {
+                    collect(written, filled, values);
+                }
+            }
+            (Value::Mapping(written), Value::Mapping(filled)) => {
+                for (key, written) in written {
+                    if let Some(filled) = filled.get(key) {
+                        collect(written, filled, values);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut values = HashMap::new();
+    for bang in config.bangs.values().chain(config.private_bangs.values()) {
+        if let Some(written) = &bang.written {
+            let written = serde_yaml::to_value(written).expect("bangs serialize to YAML");
+            let filled = serde_yaml::to_value(bang).expect("bangs serialize to YAML");
+            collect(&written, &filled, &mut values);
+        }
+    }
+    values
+}
+
+// Fill in ${...} references in a bang from the admin API or the overlay,
+// remembering the bang as written. Nothing is looked up here: a string may
+// only repeat one a config file bang uses, and gets the value it got there,
+// so admins can't read other environment variables or files.
+fn fill_bang(values: &HashMap<String, String>, name: &str, bang: &BangDetails) -> Result<BangDetails, ConfigError> {
+    fn fill(value: &mut Value, values: &HashMap<String, String>) -> Result<(), InterpolationError> {
+        match value {
+            Value::String(text) if text.contains("${") => {
+                *text = values
+                    .get(text.as_str())
+                    .cloned()
+                    .ok_or_else(|| InterpolationError::NotAllowed(text.clone()))?;
+            }
+            Value::Sequence(items) => items.iter_mut().try_for_each(|item| fill(item, values))?,
+            Value::Mapping(entries) => entries.values_mut().try_for_each(|item| fill(item, values))?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    let written = bang.as_written();
+    let mut value = serde_yaml::to_value(&written).expect("bangs serialize to YAML");
+    if !has_references(&value) {
+        return Ok(written);
+    }
+    fill(&mut value, values).map_err(|error| ConfigError::Interpolation {
+        bang: name.to_string(),
+        error,
+    })?;
+    let filled: BangDetails = serde_yaml::from_value(value).expect("filling in only changes strings");
+    Ok(BangDetails {
+        source: written.source.clone(),
+        written: Some(Box::new(written)),
+        ..filled
+    })
+}
+
+// A bang from the admin API with its ${...} references filled in, as they
+// would be once it is saved
+pub fn interpolate_bang(config: &Config, name: &str, bang: &BangDetails) -> Result<BangDetails, ConfigError> {
+    fill_bang(&interpolated_values(config), name, bang)
+}
+
+impl BangOverlay {
+    // The overlay file named by admin.overlay, or an empty overlay
+    pub fn for_config(config: &Config) -> Result<Self, AdminError> {
+        match &config.admin.overlay {
+            Some(path) => BangOverlay::load(path),
+            None => Ok(BangOverlay::default()),
+        }
+    }
+
+    // Read the overlay file; a missing file is an empty overlay
+    pub fn load(path: &str) -> Result<Self, AdminError> {
+        if !Path::new(path).exists() {
+            return Ok(BangOverlay::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+
+    // Write the overlay to a temporary file and rename it into place, so a
+    // crash never leaves a half-written file behind
+    pub fn save(&self, path: &str) -> Result<(), AdminError> {
+        let path = Path::new(path);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        std::fs::write(&temp, serde_yaml::to_string(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    // Apply the overlay on top of a config loaded from disk. Bangs in the
+    // overlay replace any public or private bang with the same name, and
+    // report the overlay file as their source. The file keeps bangs as
+    // written; their ${...} references get the values config.yml gave them.
+    pub fn apply(&self, config: &mut Config) -> Result<(), ConfigError> {
+        let values = interpolated_values(config);
+        for name in self.removed.iter().chain(self.bangs.keys()).chain(self.private_bangs.keys()) {
+            config.bangs.remove(name);
+            config.private_bangs.remove(name);
+        }
+        let source = config.admin.overlay.clone();
+        let loaded = |(name, bang): (&String, &BangDetails)| {
+            let bang = BangDetails { source: source.clone(), written: None, ..bang.clone() };
+            fill_bang(&values, name, &bang).map(|bang| (name.clone(), bang))
+        };
+        let bangs = self.bangs.iter().map(loaded).collect::<Result<Vec<_>, _>>()?;
+        let private_bangs = self.private_bangs.iter().map(loaded).collect::<Result<Vec<_>, _>>()?;
+        config.bangs.extend(bangs);
+        config.private_bangs.extend(private_bangs);
+        Ok(())
+    }
+
+    // A copy of the config with the overlay applied, checked like a config file
+    pub fn applied_to(&self, base: &Config) -> Result<Config, ConfigError> {
+        let mut config = base.clone();
+        self.apply(&mut config)?;
+        validate_config(&config)?;
+        Ok(config)
+    }
+
+    fn put(&mut self, name: &str, private: bool, bang: BangDetails) {
+        self.bangs.remove(name);
+        self.private_bangs.remove(name);
+        let target = if private { &mut self.private_bangs } else { &mut self.bangs };
+        target.insert(name.to_string(), bang);
+    }
+
+    // Add a bang that does not exist yet in the live config
+    pub fn create(&mut self, config: &Config, name: &str, private: bool, bang: BangDetails) -> Result<AdminBang, AdminError> {
+        if find_bang(config, name).is_some() {
+            return Err(AdminError::AlreadyExists(name.to_string()));
+        }
+
+        self.put(name, private, bang.clone());
+        Ok(AdminBang {
+            name: name.to_string(),
+            private,
+            bang,
+        })
+    }
+
+    // Rename a bang, move it between public and private, or replace its details
+    pub fn update(
+        &mut self,
+        config: &Config,
+        name: &str,
+        new_name: Option<&str>,
+        private: Option<bool>,
+        bang: Option<BangDetails>,
+    ) -> Result<AdminBang, AdminError> {
+        let existing = find_bang(config, name).ok_or_else(|| AdminError::NotFound(name.to_string()))?;
+
+        let new_name = new_name.unwrap_or(name);
+        if new_name != name && find_bang(config, new_name).is_some() {
+            return Err(AdminError::AlreadyExists(new_name.to_string()));
+        }
+
+        let updated = AdminBang {
+            name: new_name.to_string(),
+            private: private.unwrap_or(existing.private),
+            bang: bang.unwrap_or(existing.bang),
+        };
+        if new_name != name {
+            self.delete(config, name)?;
+        }
+        self.put(&updated.name, updated.private, updated.bang.clone());
+        Ok(updated)
+    }
+
+    pub fn delete(&mut self, config: &Config, name: &str) -> Result<(), AdminError> {
+        if find_bang(config, name).is_none() {
+            return Err(AdminError::NotFound(name.to_string()));
+        }
+
+        self.bangs.remove(name);
+        self.private_bangs.remove(name);
+        self.removed.insert(name.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::{Config, UserConfig};
+use crate::storage::now;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Who a request is acting as, based on its login parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identity {
+    Anonymous,
+    // Holder of the raw auth_token
+    Owner,
+    // A configured user presenting their scoped credential
+    User(String),
+}
+
+// Name under which the auth_token holder's data is stored
+pub const OWNER_NAME: &str = "owner";
+
+impl Identity {
+    pub fn is_authenticated(&self) -> bool {
+        !matches!(self, Identity::Anonymous)
+    }
+
+    // Name that owns per-user data such as bookmarks
+    pub fn user_name(&self) -> Option<&str> {
+        match self {
+            Identity::Anonymous => None,
+            Identity::Owner => Some(OWNER_NAME),
+            Identity::User(name) => Some(name),
+        }
+    }
+}
+
+// Credentials are signed with the auth_token and bound to the user id, so
+// they stop working once the user is revoked. Users get their own bookmarks
+// and may manage the go-links they created, through search and the APIs.
+fn sign(config: &Config, user_id: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(config.auth_token.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(b"search:");
+    mac.update(user_id.as_bytes());
+    mac
+}
+
+// Find an active user by the opaque id used in their OpenSearch URL
+pub fn find_user<'a>(config: &'a Config, user_id: &str) -> Option<(&'a String, &'a UserConfig)> {
+    if config.auth_token.is_empty() {
+        return None;
+    }
+    config
+        .users
+        .iter()
+        .find(|(_, user)| user.id == user_id && !user.revoked)
+}
+
+// Build the login credential embedded in a user's OpenSearch templates
+pub fn user_credential(config: &Config, user_id: &str) -> String {
+    let signature = sign(config, user_id).finalize().into_bytes();
+    format!("{}.{}", user_id, hex::encode(signature))
+}
+
+// Identity of a user by name, for tools that act on someone's behalf without
+// a credential. "owner" is the auth_token holder.
+pub fn identity_for(config: &Config, name: &str) -> Option<Identity> {
+    if name == OWNER_NAME {
+        return Some(Identity::Owner);
+    }
+    match config.users.get(name) {
+        Some(user) if !user.revoked => Some(Identity::User(name.to_string())),
+        _ => None,
+    }
+}
+
+// Resolve a login parameter to an identity
+pub fn identify(config: &Config, login: Option<&str>) -> Identity {
+    let login = match login {
+        Some(login) if !config.auth_token.is_empty() => login,
+        _ => return Identity::Anonymous,
+    };
+
+    if login == config.auth_token {
+        return Identity::Owner;
+    }
+
+    // Scoped credentials look like "<user id>.<hex signature>"
+    let Some((user_id, signature)) = login.rsplit_once('.') else {
+        return Identity::Anonymous;
+    };
+    let Some((name, _)) = find_user(config, user_id) else {
+        return Identity::Anonymous;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return Identity::Anonymous;
+    };
+
+    match sign(config, user_id).verify_slice(&signature) {
+        Ok(()) => Identity::User(name.clone()),
+        Err(_) => Identity::Anonymous,
+    }
+}
+
+// Whether a bearer token grants access to the admin API. The admin token is
+// separate from auth_token, so search credentials never allow config changes.
+pub fn is_admin(config: &Config, token: Option<&str>) -> bool {
+    let (false, Some(token)) = (config.admin.token.is_empty(), token) else {
+        return false;
+    };
+    // Compare fixed-length digests so the check takes the same time wherever
+    // the tokens differ
+    let expected = token_digest(&config.admin.token).finalize().into_bytes();
+    token_digest(token).verify_slice(&expected).is_ok()
+}
+
+fn token_digest(token: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(b"admin-token").expect("HMAC accepts keys of any length");
+    mac.update(token.as_bytes());
+    mac
+}
+
+// How long an admin interface session lasts, in seconds
+pub const ADMIN_SESSION_TTL: i64 = 12 * 60 * 60;
+
+fn sign_session(config: &Config, issued: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(config.admin.token.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(b"admin-session:");
+    mac.update(issued.to_string().as_bytes());
+    mac
+}
+
+// Value of the admin interface's session cookie, "<issued at>.<signature>".
+// It is signed with the admin token, so changing the token logs every
+// browser out, and it expires after ADMIN_SESSION_TTL.
+pub fn admin_session(config: &Config, issued: i64) -> Option<String> {
+    if config.admin.token.is_empty() {
+        return None;
+    }
+    let signature = sign_session(config, issued).finalize().into_bytes();
+    Some(format!("{}.{}", issued, hex::encode(signature)))
+}
+
+// Whether a session cookie value was issued for the current admin token and
+// has not expired
+pub fn is_admin_session(config: &Config, session: &str) -> bool {
+    if config.admin.token.is_empty() {
+        return false;
+    }
+    let Some((issued, signature)) = session.split_once('.') else {
+        return false;
+    };
+    let (Ok(issued), Ok(signature)) = (issued.parse::<i64>(), hex::decode(signature)) else {
+        return false;
+    };
+    let age = now() - issued;
+    (0..ADMIN_SESSION_TTL).contains(&age) && sign_session(config, issued).verify_slice(&signature).is_ok()
+}
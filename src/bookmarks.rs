@@ -0,0 +1,216 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+use crate::storage::{now, Storage};
+
+// Bangs handled natively by the bookmarks subsystem
+pub const OPEN_BANG: &str = "!b";
+pub const ADD_BANG: &str = "!ba";
+pub const DELETE_BANG: &str = "!bd";
+
+// A named URL saved by a user
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Bookmark {
+    pub name: String,
+    pub url: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug)]
+pub enum BookmarkError {
+    InvalidName(String),
+    InvalidUrl(String),
+    NotFound(String),
+    AlreadyExists(String),
+    Storage(rusqlite::Error),
+}
+
+impl fmt::Display for BookmarkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookmarkError::InvalidName(name) => write!(f, "Invalid bookmark name '{}'", name),
+            BookmarkError::InvalidUrl(url) => write!(f, "Invalid bookmark URL '{}'", url),
+            BookmarkError::NotFound(name) => write!(f, "Bookmark '{}' not found", name),
+            BookmarkError::AlreadyExists(name) => write!(f, "Bookmark '{}' already exists", name),
+            BookmarkError::Storage(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BookmarkError {}
+
+impl From<rusqlite::Error> for BookmarkError {
+    fn from(e: rusqlite::Error) -> Self {
+        BookmarkError::Storage(e)
+    }
+}
+
+// A bookmark command typed into the search box
+#[derive(Debug, PartialEq)]
+pub enum BookmarkCommand<'a> {
+    Open(&'a str),
+    Add { name: &'a str, url: &'a str },
+    Delete(&'a str),
+}
+
+// Interpret a bang and its search term as a bookmark command
+pub fn parse_command<'a>(bang: &str, term: &'a str) -> Option<BookmarkCommand<'a>> {
+    let term = term.trim();
+    match bang {
+        OPEN_BANG => Some(BookmarkCommand::Open(term)),
+        ADD_BANG => {
+            let (name, url) = term.split_once(char::is_whitespace).unwrap_or((term, ""));
+            Some(BookmarkCommand::Add { name, url: url.trim() })
+        }
+        DELETE_BANG => Some(BookmarkCommand::Delete(term)),
+        _ => None,
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), BookmarkError> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(BookmarkError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+// Accept http(s) URLs, adding https:// when the scheme is missing
+pub fn normalize_url(url: &str) -> Result<String, BookmarkError> {
+    let candidate = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    };
+
+    match Url::parse(&candidate) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {
+            Ok(parsed.to_string())
+        }
+        _ => Err(BookmarkError::InvalidUrl(url.to_string())),
+    }
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Bookmark> {
+    Ok(Bookmark {
+        name: row.get(0)?,
+        url: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+impl Storage {
+    // Save a bookmark, replacing any existing one with the same name
+    pub fn add_bookmark(&self, owner: &str, name: &str, url: &str) -> Result<Bookmark, BookmarkError> {
+        validate_name(name)?;
+        let bookmark = Bookmark {
+            name: name.to_string(),
+            url: normalize_url(url)?,
+            created_at: now(),
+        };
+
+        self.conn().execute(
+            "INSERT OR REPLACE INTO bookmarks (owner, name, url, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![owner, bookmark.name, bookmark.url, bookmark.created_at],
+        )?;
+        Ok(bookmark)
+    }
+
+    // Rename a bookmark and/or change its URL
+    pub fn update_bookmark(
+        &self,
+        owner: &str,
+        name: &str,
+        new_name: Option<&str>,
+        url: Option<&str>,
+    ) -> Result<Bookmark, BookmarkError> {
+        let existing = self
+            .get_bookmark(owner, name)?
+            .ok_or_else(|| BookmarkError::NotFound(name.to_string()))?;
+
+        let new_name = new_name.unwrap_or(&existing.name);
+        validate_name(new_name)?;
+        if !new_name.eq_ignore_ascii_case(&existing.name) && self.get_bookmark(owner, new_name)?.is_some() {
+            return Err(BookmarkError::AlreadyExists(new_name.to_string()));
+        }
+        let url = match url {
+            Some(url) => normalize_url(url)?,
+            None => existing.url,
+        };
+
+        self.conn().execute(
+            "UPDATE bookmarks SET name = ?3, url = ?4 WHERE owner = ?1 AND name = ?2 COLLATE NOCASE",
+            params![owner, name, new_name, url],
+        )?;
+        Ok(Bookmark {
+            name: new_name.to_string(),
+            url,
+            created_at: existing.created_at,
+        })
+    }
+
+    // Delete a bookmark, returning whether it existed
+    pub fn delete_bookmark(&self, owner: &str, name: &str) -> Result<bool, BookmarkError> {
+        let deleted = self.conn().execute(
+            "DELETE FROM bookmarks WHERE owner = ?1 AND name = ?2 COLLATE NOCASE",
+            params![owner, name],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    pub fn get_bookmark(&self, owner: &str, name: &str) -> Result<Option<Bookmark>, BookmarkError> {
+        let bookmark = self
+            .conn()
+            .query_row(
+                "SELECT name, url, created_at FROM bookmarks WHERE owner = ?1 AND name = ?2 COLLATE NOCASE",
+                params![owner, name],
+                from_row,
+            )
+            .optional()?;
+        Ok(bookmark)
+    }
+
+    pub fn list_bookmarks(&self, owner: &str) -> Result<Vec<Bookmark>, BookmarkError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT name, url, created_at FROM bookmarks WHERE owner = ?1 ORDER BY name COLLATE NOCASE",
+        )?;
+        let bookmarks = stmt
+            .query_map(params![owner], from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(bookmarks)
+    }
+}
+
+// Score how well a bookmark name matches a query; None means no match
+fn match_score(name: &str, query: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    if name == query {
+        return Some(0);
+    }
+    if name.starts_with(query) {
+        return Some(1);
+    }
+    if name.contains(query) {
+        return Some(2);
+    }
+
+    // Fall back to matching the query's characters in order
+    let mut chars = name.chars();
+    query
+        .chars()
+        .all(|q| chars.any(|c| c == q))
+        .then_some(3)
+}
+
+// Bookmarks whose name fuzzily matches the query, best matches first
+pub fn fuzzy_matches(bookmarks: Vec<Bookmark>, query: &str) -> Vec<Bookmark> {
+    let query = query.trim().to_lowercase();
+    let mut scored: Vec<(u8, Bookmark)> = bookmarks
+        .into_iter()
+        .filter_map(|b| match_score(&b.name, &query).map(|score| (score, b)))
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    scored.into_iter().map(|(_, b)| b).collect()
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::auth::{identity_for, Identity};
+use crate::config::Config;
+use crate::resolver::{Action, BangResolver, Matched, Resolution};
+
+// A routing test: a query and where it should end up
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TestCase {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub query: String,
+    // Who searches: "owner" or a configured user; anonymous when unset
+    #[serde(default)]
+    pub user: Option<String>,
+    // Expected final URL
+    #[serde(default)]
+    pub url: Option<String>,
+    // Expect nothing to match, so the query goes to the default engine
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+// A YAML file of test cases, optionally naming the config it tests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaseFile {
+    // Config file to test, relative to the case file
+    #[serde(default)]
+    pub config: Option<String>,
+    pub cases: Vec<TestCase>,
+}
+
+// A case that expects neither a URL nor a fallback
+#[derive(Debug, PartialEq, Eq)]
+pub struct MissingExpectation(pub String);
+
+impl fmt::Display for MissingExpectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Test case '{}' needs a url or fallback: true", self.0)
+    }
+}
+
+impl std::error::Error for MissingExpectation {}
+
+// A case whose result differs from what it expects
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseFailure {
+    // The case's label, see TestCase::label
+    pub case: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for CaseFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FAIL {}", self.case)?;
+        writeln!(f, "  expected: {}", self.expected)?;
+        write!(f, "  actual:   {}", self.actual)
+    }
+}
+
+impl TestCase {
+    // How the case is shown in reports
+    pub fn label(&self) -> String {
+        let label = self.name.clone().unwrap_or_else(|| self.query.clone());
+        match &self.user {
+            Some(user) => format!("{} (as {})", label, user),
+            None => label,
+        }
+    }
+}
+
+impl CaseFile {
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file: CaseFile = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+        if let Some(case) = file.cases.iter().find(|case| case.url.is_none() && !case.fallback) {
+            return Err(MissingExpectation(case.label()).into());
+        }
+        Ok(file)
+    }
+
+    // The config named in a case file loaded from `path`
+    pub fn config_path(&self, path: &str) -> Option<PathBuf> {
+        let config = self.config.as_ref()?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        Some(dir.join(config))
+    }
+}
+
+// What a resolution amounts to, in a form that can be compared and printed
+fn outcome(resolution: &Resolution) -> String {
+    let outcome = match &resolution.action {
+        Action::Redirect(url) => url.clone(),
+        Action::Post(form) => format!("POST {}", form.action),
+        Action::Blocked { target, violation } => format!("blocked {} ({})", target, violation),
+        Action::Invalid(message) => format!("error: {}", message),
+        Action::Bookmark => "bookmark command".to_string(),
+    };
+    format!("{} [{}]", outcome, resolution.reason)
+}
+
+// Run one case through the resolver
+pub fn run_case(config: &Config, case: &TestCase) -> Result<(), CaseFailure> {
+    let failure = |expected: String, actual: String| CaseFailure {
+        case: case.label(),
+        expected,
+        actual,
+    };
+
+    let identity = match &case.user {
+        Some(name) => identity_for(config, name)
+            .ok_or_else(|| failure(format!("user '{}'", name), "no such user".to_string()))?,
+        None => Identity::Anonymous,
+    };
+    let resolution = BangResolver::new(config).resolve(&case.query, &identity);
+
+    if case.fallback && resolution.matched != Matched::Default {
+        return Err(failure("fallback to the default engine".to_string(), outcome(&resolution)));
+    }
+    if let Some(url) = &case.url {
+        if resolution.url() != Some(url.as_str()) {
+            return Err(failure(url.clone(), outcome(&resolution)));
+        }
+    }
+    Ok(())
+}
+
+// Run every case, returning the ones that failed
+pub fn run_cases(config: &Config, cases: &[TestCase]) -> Vec<CaseFailure> {
+    cases
+        .iter()
+        .filter_map(|case| run_case(config, case).err())
+        .collect()
+}
@@ -1,48 +1,607 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use tokio::fs::File;
-use tokio::io::AsyncReadExt;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::auth::OWNER_NAME;
+use crate::deprecation::parse_date;
+use crate::formats::ConfigFormat;
+use crate::interpolate::{has_references, interpolate_value, redact, InterpolationError};
+use crate::policy::RedirectPolicy;
+use crate::rewrite::RewriteStep;
+use crate::rules::Rule;
+use crate::suggest::SuggestProvider;
+
+// How a bang turns its search term into a redirect
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BangMode {
+    // Substitute the encoded term into the URL template
+    #[default]
+    Search,
+    // Treat the term itself as a URL or hostname to go to
+    Navigate,
+}
+
+// HTTP method used to send the search to the target site
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    // Submit an HTML form, for sites that only accept POSTed searches
+    Post,
+}
 
 // Bang details structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct BangDetails {
+    // May be left out when replaced_by is set
+    #[serde(default)]
     pub url: String,
     #[serde(default)]
     pub name: String,
     #[serde(default)]
     pub icon: String,
+    #[serde(default)]
+    pub mode: BangMode,
+    // Overrides the global redirect_policy for this bang
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<RedirectPolicy>,
+    // Where a bang without a search term goes, e.g. a bare "!gh"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_url: Option<String>,
+    #[serde(default)]
+    pub method: HttpMethod,
+    // Form fields sent with method: post; values may contain {searchTerms}
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub form: BTreeMap<String, String>,
+    // What the bang searches, for listings and OpenSearch descriptions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // Group shown and filtered on in listings, e.g. "code" or "shopping"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Sample queries, e.g. "!gh tokio"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    // Still works, but is flagged in listings so people move off it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    // Bang to send searches to instead, e.g. "!x" for a retired "!twitter"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    // Last day (YYYY-MM-DD) the deprecated key still works; after that it
+    // only shows a notice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_until: Option<String>,
+    // Show a notice page the first time someone uses the deprecated key
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub notice: bool,
+    // File the bang was defined in, filled in when the config is loaded
+    #[serde(skip)]
+    pub source: Option<String>,
+    // The bang before ${...} interpolation, when any of its values used it.
+    // Listings and the admin overlay use this so interpolated hostnames and
+    // secrets stay out of them.
+    #[serde(skip)]
+    pub written: Option<Box<BangDetails>>,
+}
+
+impl BangDetails {
+    // The bang as written, before ${...} interpolation, for anything that is
+    // shown to people or saved back to disk
+    pub fn as_written(&self) -> BangDetails {
+        match &self.written {
+            Some(written) => BangDetails {
+                source: self.source.clone(),
+                ..(**written).clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    // Deprecated explicitly, or implied by a replacement or end date
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated || self.replaced_by.is_some() || self.deprecated_until.is_some()
+    }
+
+    // The configured home_url, or the origin of the URL template
+    pub fn home(&self) -> Option<String> {
+        self.home_url.clone().or_else(|| self.origin())
+    }
+
+    // Scheme and host of the URL template, e.g. "https://github.com/"
+    pub fn origin(&self) -> Option<String> {
+        let url = Url::parse(&self.url).ok()?;
+        // Templates like "https://{searchTerms}" have no fixed host
+        let host = url.host_str()?;
+        if host.contains(['{', '}']) {
+            return None;
+        }
+        Some(format!("{}/", url.origin().ascii_serialization()))
+    }
+}
+
+// Default search engine used when no bang matches
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct DefaultEngine {
+    pub url: String,
+    #[serde(default)]
+    pub suggest: SuggestProvider,
+}
+
+impl Default for DefaultEngine {
+    fn default() -> Self {
+        DefaultEngine {
+            url: "https://www.google.com/search?q={searchTerms}".to_string(),
+            suggest: SuggestProvider::None,
+        }
+    }
+}
+
+// Fields of the OpenSearch description document
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct OpenSearchConfig {
+    pub short_name: String,
+    pub long_name: Option<String>,
+    pub description: String,
+    pub icon: Option<String>,
+    pub contact: Option<String>,
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    pub input_encoding: String,
+}
+
+impl Default for OpenSearchConfig {
+    fn default() -> Self {
+        OpenSearchConfig {
+            short_name: "Bang Search".to_string(),
+            long_name: None,
+            description: "Search with bangs".to_string(),
+            icon: None,
+            contact: None,
+            tags: Vec::new(),
+            language: None,
+            input_encoding: "UTF-8".to_string(),
+        }
+    }
+}
+
+// A user with their own OpenSearch document and search credential
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct UserConfig {
+    // Opaque id used in /u/{id}/opensearch.xml
+    pub id: String,
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+// Where user data such as bookmarks is stored
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub struct StorageConfig {
+    // Path to the SQLite database; data is kept in memory when unset
+    pub path: Option<String>,
+}
+
+// How go-links are reached from the search box
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct LinksConfig {
+    // Bang whose first word is looked up as a link name before the bang itself
+    pub bang: String,
+    // Query prefix that always resolves a link, e.g. "go/wiki"
+    pub prefix: String,
+}
+
+impl Default for LinksConfig {
+    fn default() -> Self {
+        LinksConfig {
+            bang: "!go".to_string(),
+            prefix: "go/".to_string(),
+        }
+    }
+}
+
+// Rules for direct navigation to URLs typed into the search box
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct NavigationConfig {
+    // Schemes that may be navigated to; anything else is rejected
+    pub schemes: Vec<String>,
+    // When non-empty, only these hosts (and their subdomains) are allowed
+    pub allow_hosts: Vec<String>,
+    // Hosts (and their subdomains) that are never navigated to
+    pub deny_hosts: Vec<String>,
+    // Navigate directly when a query without a bang looks like a URL
+    pub auto_navigate: bool,
+}
+
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        NavigationConfig {
+            schemes: vec!["http".to_string(), "https".to_string()],
+            allow_hosts: Vec::new(),
+            deny_hosts: Vec::new(),
+            auto_navigate: false,
+        }
+    }
+}
+
+// Runtime management of bangs through the /admin API
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct AdminConfig {
+    // Bearer token for /admin, separate from auth_token; the API is disabled while empty
+    pub token: String,
+    // YAML file holding the bangs changed through the API, applied on top of
+    // this config at startup. Changes only last until a restart when unset.
+    pub overlay: Option<String>,
 }
 
 // Configuration structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Config {
+    // Other config files and directories to merge, relative to this file.
+    // The loader handles these, so a loaded config has none left.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default)]
     pub bangs: HashMap<String, BangDetails>,
     #[serde(default)]
     pub private_bangs: HashMap<String, BangDetails>,
     #[serde(default)]
     pub auth_token: String,
     pub host_url: Option<String>,
+    #[serde(default)]
+    pub default_engine: DefaultEngine,
+    #[serde(default)]
+    pub opensearch: OpenSearchConfig,
+    #[serde(default)]
+    pub users: HashMap<String, UserConfig>,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub links: LinksConfig,
+    #[serde(default)]
+    pub navigation: NavigationConfig,
+    #[serde(default)]
+    pub redirect_policy: RedirectPolicy,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub rewrites: Vec<RewriteStep>,
+    #[serde(default)]
+    pub admin: AdminConfig,
 }
 
-// Load configuration from a YAML file
-pub async fn load_config(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    // Check if file exists
-    if !Path::new(path).exists() {
-        return Ok(default_config());
+// A problem with a bang definition, found at load time or by the admin API
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidBangName(String),
+    InvalidBangUrl { bang: String, url: String },
+    FormWithoutPost(String),
+    DuplicateBang(String),
+    InvalidDate { bang: String, date: String },
+    // replaced_by names a bang that does not exist or is itself replaced
+    InvalidReplacement { bang: String, replaced_by: String },
+    // A ${...} reference in a bang saved through the admin API
+    Interpolation { bang: String, error: InterpolationError },
+    // A user named like the auth_token holder, whose data they would share
+    ReservedUserName(String),
+    // The same setting or bang in two config files, e.g. "bangs.!g"
+    Conflict { key: String, first: String, second: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidBangName(bang) => {
+                write!(f, "Invalid bang name '{}': bangs start with '!' and contain no spaces", bang)
+            }
+            ConfigError::InvalidBangUrl { bang, url } => write!(f, "Bang '{}' has an invalid URL '{}'", bang, url),
+            ConfigError::FormWithoutPost(bang) => write!(f, "Bang '{}' has form fields but does not use method: post", bang),
+            ConfigError::DuplicateBang(bang) => write!(f, "Bang '{}' is both public and private", bang),
+            ConfigError::InvalidDate { bang, date } => {
+                write!(f, "Bang '{}' has an invalid deprecated_until date '{}': use YYYY-MM-DD", bang, date)
+            }
+            ConfigError::Interpolation { bang, error } => write!(f, "Bang '{}': {}", bang, error),
+            ConfigError::InvalidReplacement { bang, replaced_by } => {
+                write!(f, "Bang '{}' is replaced by '{}', which is not a bang that can be used instead", bang, replaced_by)
+            }
+            ConfigError::ReservedUserName(name) => {
+                write!(f, "User name '{}' is reserved for the auth_token holder", name)
+            }
+            ConfigError::Conflict { key, first, second } => {
+                write!(f, "'{}' is defined in both {} and {}", key, first, second)
+            }
+        }
     }
-    
-    // Open the file
-    let mut file = File::open(path).await?;
-    
-    // Read the file contents
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).await?;
-    
-    // Parse the YAML into our Config structure
-    let config: Config = serde_yaml::from_str(&contents)?;
-    
-    Ok(config)
+}
+
+impl std::error::Error for ConfigError {}
+
+// Check a single bang definition
+pub fn validate_bang(key: &str, details: &BangDetails) -> Result<(), ConfigError> {
+    let valid_key = key.len() > 1 && key.starts_with('!') && !key.contains(char::is_whitespace);
+    if !valid_key {
+        return Err(ConfigError::InvalidBangName(key.to_string()));
+    }
+
+    // Templates are checked with a term filled in, since "https://{searchTerms}" is not a URL
+    let invalid_url = |url: &str| Url::parse(&url.replace("{searchTerms}", "test")).is_err();
+    // A replaced bang needs no URL of its own
+    let url = Some(&details.url).filter(|url| !url.is_empty() || details.replaced_by.is_none());
+    for url in url.into_iter().chain(&details.home_url) {
+        if invalid_url(url) {
+            return Err(ConfigError::InvalidBangUrl {
+                bang: key.to_string(),
+                url: url.clone(),
+            });
+        }
+    }
+
+    if !details.form.is_empty() && details.method != HttpMethod::Post {
+        return Err(ConfigError::FormWithoutPost(key.to_string()));
+    }
+
+    if let Some(date) = details.deprecated_until.as_ref().filter(|date| parse_date(date).is_none()) {
+        return Err(ConfigError::InvalidDate {
+            bang: key.to_string(),
+            date: date.clone(),
+        });
+    }
+    Ok(())
+}
+
+// Check every bang in a config
+pub fn validate_config(config: &Config) -> Result<(), ConfigError> {
+    for (key, details) in config.bangs.iter().chain(&config.private_bangs) {
+        validate_bang(key, details)?;
+    }
+    if let Some(key) = config.bangs.keys().find(|key| config.private_bangs.contains_key(*key)) {
+        return Err(ConfigError::DuplicateBang(key.clone()));
+    }
+
+    // Replacements are followed one step, so they must be usable bangs
+    for (key, details) in config.bangs.iter().chain(&config.private_bangs) {
+        let Some(replaced_by) = &details.replaced_by else {
+            continue;
+        };
+        let replacement = config.bangs.get(replaced_by).or_else(|| config.private_bangs.get(replaced_by));
+        if replacement.is_none_or(|r| r.replaced_by.is_some()) {
+            return Err(ConfigError::InvalidReplacement {
+                bang: key.clone(),
+                replaced_by: replaced_by.clone(),
+            });
+        }
+    }
+
+    if let Some(name) = config.users.keys().find(|name| name.as_str() == OWNER_NAME) {
+        return Err(ConfigError::ReservedUserName(name.clone()));
+    }
+    Ok(())
+}
+
+// Top-level settings whose entries are merged one by one across files
+const MERGED_MAPS: [&str; 3] = ["bangs", "private_bangs", "users"];
+// Top-level lists that are concatenated in load order
+const MERGED_LISTS: [&str; 2] = ["rules", "rewrites"];
+
+// Merges config files and remembers which file defined each setting
+#[derive(Default)]
+struct ConfigLoader {
+    merged: Mapping,
+    // "auth_token" or "bangs.!g" -> the file that set it
+    origins: HashMap<String, String>,
+    // "bangs.!g" -> the bang as written, for bangs with ${...} in any value
+    written: HashMap<String, Value>,
+    // Values filled in by interpolation, kept out of error messages
+    secrets: Vec<String>,
+    loaded: HashSet<PathBuf>,
+}
+
+impl ConfigLoader {
+    // Merge a file, then the files and directories it includes
+    fn load_file(&mut self, path: &Path, format: ConfigFormat) -> Result<(), Box<dyn std::error::Error>> {
+        // Each file is merged once, so include cycles and repeats are harmless
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !self.loaded.insert(canonical) {
+            return Ok(());
+        }
+        let source = path.display().to_string();
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", source, e))?;
+        let mut value = format.parse(&contents).map_err(|e| format!("{}: {}", source, e))?;
+        self.record_written(&value);
+        interpolate_value(&mut value, &mut self.secrets).map_err(|e| format!("{}: {}", source, e))?;
+        let mut settings = match value {
+            Value::Mapping(settings) => settings,
+            Value::Null => Mapping::new(),
+            _ => return Err(format!("{}: expected a mapping of settings", source).into()),
+        };
+
+        let includes: Vec<String> = match settings.remove("include") {
+            Some(include) => serde_yaml::from_value(include).map_err(|e| format!("{}: include: {}", source, e))?,
+            None => Vec::new(),
+        };
+        self.merge(settings, &source)?;
+
+        // Includes are relative to the including file, and in its format
+        // unless their extension says otherwise
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for include in includes {
+            let path = dir.join(include);
+            if path.is_dir() {
+                self.load_dir(&path)?;
+            } else {
+                self.load_file(&path, ConfigFormat::from_extension(&path).unwrap_or(format))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Merge every .yml, .yaml, .toml or .json file in a directory, in file
+    // name order
+    fn load_dir(&mut self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut files: Vec<(PathBuf, ConfigFormat)> = std::fs::read_dir(dir)
+            .map_err(|e| format!("{}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter_map(|path| ConfigFormat::from_extension(&path).map(|format| (path, format)))
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        for (file, format) in files {
+            self.load_file(&file, format)?;
+        }
+        Ok(())
+    }
+
+    fn record_written(&mut self, value: &Value) {
+        for section in ["bangs", "private_bangs"] {
+            let Some(bangs) = value.get(section).and_then(Value::as_mapping) else { continue };
+            for (key, details) in bangs.iter().filter(|(_, details)| has_references(details)) {
+                let key = format!("{}.{}", section, key.as_str().unwrap_or_default());
+                self.written.insert(key, details.clone());
+            }
+        }
+    }
+
+    // Record where a setting came from, refusing one that is already set
+    fn claim(&mut self, key: String, source: &str) -> Result<(), ConfigError> {
+        if let Some(first) = self.origins.get(&key) {
+            return Err(ConfigError::Conflict {
+                key,
+                first: first.clone(),
+                second: source.to_string(),
+            });
+        }
+        self.origins.insert(key, source.to_string());
+        Ok(())
+    }
+
+    fn merge(&mut self, settings: Mapping, source: &str) -> Result<(), ConfigError> {
+        for (key, value) in settings {
+            let name = key.as_str().unwrap_or_default().to_string();
+            match value {
+                Value::Mapping(entries) if MERGED_MAPS.contains(&name.as_str()) => {
+                    for entry in entries.keys() {
+                        self.claim(format!("{}.{}", name, entry.as_str().unwrap_or_default()), source)?;
+                    }
+                    match self.merged.get_mut(&key).and_then(Value::as_mapping_mut) {
+                        Some(merged) => merged.extend(entries),
+                        None => {
+                            self.merged.insert(key, Value::Mapping(entries));
+                        }
+                    }
+                }
+                Value::Sequence(items) if MERGED_LISTS.contains(&name.as_str()) => {
+                    match self.merged.get_mut(&key).and_then(Value::as_sequence_mut) {
+                        Some(merged) => merged.extend(items),
+                        None => {
+                            self.merged.insert(key, Value::Sequence(items));
+                        }
+                    }
+                }
+                value => {
+                    self.claim(name, source)?;
+                    self.merged.insert(key, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, paths: &[PathBuf], format: Option<ConfigFormat>) -> Result<Config, Box<dyn std::error::Error>> {
+        for path in paths {
+            let format = format.or_else(|| ConfigFormat::from_extension(path)).unwrap_or(ConfigFormat::Yaml);
+            self.load_file(path, format)?;
+            let fragments = path.parent().unwrap_or(Path::new("")).join("config.d");
+            if fragments.is_dir() {
+                self.load_dir(&fragments)?;
+            }
+        }
+
+        let merged = std::mem::take(&mut self.merged);
+        let mut config: Config = serde_yaml::from_value(Value::Mapping(merged))?;
+
+        for (section, bangs) in [("bangs", &mut config.bangs), ("private_bangs", &mut config.private_bangs)] {
+            for (key, details) in bangs {
+                let key = format!("{}.{}", section, key);
+                details.source = self.origins.get(&key).cloned();
+                if let Some(written) = self.written.remove(&key) {
+                    details.written = Some(Box::new(written_bang(&written, details)));
+                }
+            }
+        }
+        validate_config(&config)?;
+        Ok(config)
+    }
+}
+
+// A loaded bang with the values it had as written. Settings that can't be
+// read as written, like `mode: "${MODE}"`, keep their filled in value.
+fn written_bang(written: &Value, filled: &BangDetails) -> BangDetails {
+    let mut bang = serde_yaml::to_value(filled).expect("bangs serialize to YAML");
+    let fields = written.as_mapping().into_iter().flatten().filter(|(_, value)| has_references(value));
+    for (field, value) in fields {
+        let mut candidate = bang.clone();
+        if let Some(candidate) = candidate.as_mapping_mut() {
+            candidate.insert(field.clone(), value.clone());
+        }
+        if serde_yaml::from_value::<BangDetails>(candidate.clone()).is_ok() {
+            bang = candidate;
+        }
+    }
+    serde_yaml::from_value(bang).expect("bangs read back what they serialize")
+}
+
+// Load configuration from a YAML, TOML or JSON file
+pub async fn load_config(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
+    read_config(path)
+}
+
+// Like load_config, but read the files in `format` whatever their extension
+pub async fn load_config_as(path: &str, format: Option<ConfigFormat>) -> Result<Config, Box<dyn std::error::Error>> {
+    read_config_as(path, format)
+}
+
+// Blocking version of load_config, for tools that don't run an async runtime.
+//
+// `path` may list several files separated like $PATH. They are merged in
+// order: each file, then the files and directories named in its `include:`
+// list, then the config files in a config.d directory next to it, by name.
+// Bangs and settings may only be defined once across all of them; rules and
+// rewrites are concatenated in load order. Every file listed must exist.
+//
+// Each file's format comes from its extension (.yml, .yaml, .toml or .json),
+// defaulting to YAML. Strings may use ${VAR}, ${VAR:-default} and
+// ${file:/run/secrets/name} (see interpolate.rs); a reference without a value
+// is an error.
+pub fn read_config(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
+    read_config_as(path, None)
+}
+
+// Blocking version of load_config_as
+pub fn read_config_as(path: &str, format: Option<ConfigFormat>) -> Result<Config, Box<dyn std::error::Error>> {
+    let paths: Vec<PathBuf> = std::env::split_paths(path).collect();
+
+    // Errors can quote config values, which may now include secrets
+    let mut loader = ConfigLoader::default();
+    loader
+        .load(&paths, format)
+        .map_err(|e| redact(&e.to_string(), &loader.secrets).into())
+}
+
+// JSON Schema for config files, for editors to validate against. It applies
+// to every format, and is kept in the repository as config.schema.json.
+pub fn config_schema() -> serde_json::Value {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_value(schema).unwrap_or_default()
 }
 
 // Create a default configuration
@@ -55,42 +614,59 @@ pub fn default_config() -> Config {
         url: "https://www.google.com/search?q={searchTerms}".to_string(),
         name: "Google".to_string(),
         icon: "google".to_string(),
+        ..Default::default()
     });
     
     bangs.insert("!ddg".to_string(), BangDetails {
         url: "https://duckduckgo.com/?q={searchTerms}".to_string(),
         name: "DuckDuckGo".to_string(),
         icon: "duck".to_string(),
+        ..Default::default()
     });
     
     bangs.insert("!yt".to_string(), BangDetails {
         url: "https://www.youtube.com/results?search_query={searchTerms}".to_string(),
         name: "YouTube".to_string(),
         icon: "youtube".to_string(),
+        ..Default::default()
     });
     
     bangs.insert("!gh".to_string(), BangDetails {
         url: "https://github.com/search?q={searchTerms}".to_string(),
         name: "GitHub".to_string(),
         icon: "github".to_string(),
+        ..Default::default()
     });
     
     bangs.insert("!w".to_string(), BangDetails {
         url: "https://en.wikipedia.org/wiki/Special:Search?search={searchTerms}".to_string(),
         name: "Wikipedia".to_string(),
         icon: "wikipedia".to_string(),
+        ..Default::default()
     });
     
     bangs.insert("!maps".to_string(), BangDetails {
         url: "https://www.google.com/maps?q={searchTerms}&source=web".to_string(),
         name: "Google Maps".to_string(),
         icon: "map".to_string(),
+        ..Default::default()
     });
     
     Config { 
+        include: Vec::new(),
         bangs,
         private_bangs,
         auth_token: "".to_string(),
         host_url: None,
+        default_engine: DefaultEngine::default(),
+        opensearch: OpenSearchConfig::default(),
+        users: HashMap::new(),
+        storage: StorageConfig::default(),
+        links: LinksConfig::default(),
+        navigation: NavigationConfig::default(),
+        redirect_policy: RedirectPolicy::default(),
+        rules: Vec::new(),
+        rewrites: Vec::new(),
+        admin: AdminConfig::default(),
     }
 } 
\ No newline at end of file
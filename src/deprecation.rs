@@ -0,0 +1,144 @@
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::config::{BangDetails, Config};
+use crate::storage::{now, Storage};
+
+// A deprecated bang that a query used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Deprecation {
+    // The key as typed, e.g. "!twitter"
+    pub key: String,
+    pub replaced_by: Option<String>,
+    pub until: Option<String>,
+    // Past its deprecated_until date, so searches no longer go through
+    pub retired: bool,
+    // Show a notice page the first time the key is used
+    pub notice: bool,
+}
+
+impl Deprecation {
+    pub fn for_bang(key: &str, details: &BangDetails) -> Option<Self> {
+        if !details.is_deprecated() {
+            return None;
+        }
+        let retired = details
+            .deprecated_until
+            .as_deref()
+            .and_then(parse_date)
+            .is_some_and(|until| today() > until);
+        Some(Deprecation {
+            key: key.to_string(),
+            replaced_by: details.replaced_by.clone(),
+            until: details.deprecated_until.clone(),
+            retired,
+            notice: details.notice,
+        })
+    }
+
+    // What to tell the person using the key
+    pub fn message(&self) -> String {
+        let status = match &self.until {
+            Some(until) if self.retired => format!("{} was retired after {}", self.key, until),
+            Some(until) => format!("{} is deprecated and stops working after {}", self.key, until),
+            None => format!("{} is deprecated", self.key),
+        };
+        match &self.replaced_by {
+            Some(replacement) => format!("{}; use {} instead", status, replacement),
+            None => status,
+        }
+    }
+}
+
+// Days since the Unix epoch for a YYYY-MM-DD date
+pub fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if date.len() != 10 || !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    // Count from March so the leap day comes last in the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn today() -> i64 {
+    now().div_euclid(86_400)
+}
+
+// A deprecated bang with how often it is still used, to tell when it can
+// be deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeprecatedBang {
+    pub key: String,
+    pub replaced_by: Option<String>,
+    pub deprecated_until: Option<String>,
+    pub retired: bool,
+    pub private: bool,
+    pub uses: i64,
+    pub last_used: Option<i64>,
+}
+
+impl Storage {
+    // Count a search that used a deprecated bang
+    pub fn record_bang_use(&self, key: &str) -> rusqlite::Result<()> {
+        self.conn().execute(
+            "INSERT INTO bang_uses (key, uses, last_used) VALUES (?1, 1, ?2)
+             ON CONFLICT (key) DO UPDATE SET uses = uses + 1, last_used = ?2",
+            params![key, now()],
+        )?;
+        Ok(())
+    }
+
+    // Use counts and last use by bang key
+    pub fn bang_uses(&self) -> rusqlite::Result<HashMap<String, (i64, i64)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT key, uses, last_used FROM bang_uses")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+        rows.collect()
+    }
+}
+
+// The deprecated bangs visible to the caller with their use counts, sorted
+// by key
+pub fn deprecated_bangs(config: &Config, storage: &Storage, include_private: bool) -> rusqlite::Result<Vec<DeprecatedBang>> {
+    let uses = storage.bang_uses()?;
+    let public = config.bangs.iter().map(|bang| (bang, false));
+    let private = config.private_bangs.iter().filter(|_| include_private).map(|bang| (bang, true));
+
+    let mut bangs: Vec<_> = public
+        .chain(private)
+        .filter_map(|((key, details), private)| {
+            let deprecation = Deprecation::for_bang(key, details)?;
+            let (uses, last_used) = uses.get(key).map_or((0, None), |(uses, last)| (*uses, Some(*last)));
+            Some(DeprecatedBang {
+                key: deprecation.key,
+                replaced_by: deprecation.replaced_by,
+                deprecated_until: deprecation.until,
+                retired: deprecation.retired,
+                private,
+                uses,
+                last_used,
+            })
+        })
+        .collect();
+    bangs.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(bangs)
+}
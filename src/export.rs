@@ -0,0 +1,203 @@
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::{BangDetails, BangMode, Config, HttpMethod, OpenSearchConfig};
+use crate::opensearch::{render_description, DocumentUrls};
+use crate::pages::escape_html;
+
+// Formats bangs can be exported to, so keyword searches work in browsers
+// and launchers without going through the redirect server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // Netscape bookmark file with SHORTCUTURL keywords, for Firefox
+    Bookmarks,
+    // ManagedSearchEngines policy for Chrome
+    Chrome,
+    // Quicklinks to import into Raycast
+    Raycast,
+    // A page of alfred://customsearch links, one per bang
+    Alfred,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownExportFormat(pub String);
+
+impl fmt::Display for UnknownExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown export format '{}': use bookmarks, chrome, raycast or alfred", self.0)
+    }
+}
+
+impl std::error::Error for UnknownExportFormat {}
+
+impl FromStr for ExportFormat {
+    type Err = UnknownExportFormat;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "bookmarks" => Ok(ExportFormat::Bookmarks),
+            "chrome" => Ok(ExportFormat::Chrome),
+            "raycast" => Ok(ExportFormat::Raycast),
+            "alfred" => Ok(ExportFormat::Alfred),
+            _ => Err(UnknownExportFormat(name.to_string())),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Bookmarks | ExportFormat::Alfred => "text/html; charset=utf-8",
+            ExportFormat::Chrome | ExportFormat::Raycast => "application/json",
+        }
+    }
+
+    // Name to save the export under
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ExportFormat::Bookmarks => "bangs-bookmarks.html",
+            ExportFormat::Chrome => "bangs-chrome-policy.json",
+            ExportFormat::Raycast => "bangs-raycast-quicklinks.json",
+            ExportFormat::Alfred => "bangs-alfred.html",
+        }
+    }
+}
+
+// A bang as a browser keyword: "!gh" is typed as "gh"
+fn keyword(key: &str) -> &str {
+    key.trim_start_matches('!')
+}
+
+fn display_name<'a>(key: &'a str, bang: &'a BangDetails) -> &'a str {
+    if bang.name.is_empty() {
+        key
+    } else {
+        &bang.name
+    }
+}
+
+// Whether a bang works as a plain search URL template. POST bangs are left
+// out, since keyword bookmarks can only open a URL, and so are navigate bangs,
+// whose term is the target rather than something to search for. Replaced
+// bangs should not be added anywhere new.
+pub fn can_export(bang: &BangDetails) -> bool {
+    bang.method == HttpMethod::Get && bang.mode == BangMode::Search && bang.replaced_by.is_none()
+}
+
+// The bangs that can be exported, sorted by key
+pub fn exported_bangs(config: &Config, include_private: bool) -> Vec<(&String, &BangDetails)> {
+    let private = config.private_bangs.iter().filter(|_| include_private);
+    let mut bangs: Vec<_> = config
+        .bangs
+        .iter()
+        .chain(private)
+        .filter(|(_, bang)| can_export(bang))
+        .collect();
+    bangs.sort_by(|a, b| a.0.cmp(b.0));
+    bangs
+}
+
+fn netscape_bookmarks(bangs: &[(&String, &BangDetails)]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n<H1>Bookmarks</H1>\n<DL><p>\n    <DT><H3>Bangs</H3>\n    <DL><p>\n",
+    );
+    for (key, bang) in bangs {
+        html.push_str(&format!(
+            "        <DT><A HREF=\"{}\" SHORTCUTURL=\"{}\">{}</A>\n",
+            escape_html(&bang.url.replace("{searchTerms}", "%s")),
+            escape_html(keyword(key)),
+            escape_html(display_name(key, bang)),
+        ));
+        if let Some(description) = &bang.description {
+            html.push_str(&format!("        <DD>{}\n", escape_html(description)));
+        }
+    }
+    html.push_str("    </DL><p>\n</DL><p>\n");
+    html
+}
+
+fn chrome_policy(bangs: &[(&String, &BangDetails)]) -> serde_json::Value {
+    let engines: Vec<_> = bangs
+        .iter()
+        .map(|(key, bang)| {
+            json!({
+                "name": display_name(key, bang),
+                "keyword": keyword(key),
+                "search_url": bang.url,
+            })
+        })
+        .collect();
+    json!({ "ManagedSearchEngines": engines })
+}
+
+fn raycast_quicklinks(bangs: &[(&String, &BangDetails)]) -> serde_json::Value {
+    let links: Vec<_> = bangs
+        .iter()
+        .map(|(key, bang)| {
+            json!({
+                "name": format!("{} ({})", display_name(key, bang), key),
+                "link": bang.url.replace("{searchTerms}", "{argument name=\"query\"}"),
+            })
+        })
+        .collect();
+    json!(links)
+}
+
+fn alfred_links(bangs: &[(&String, &BangDetails)]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Bangs for Alfred</title></head>\n<body>\n\
+         <h1>Bangs for Alfred</h1>\n<p>Open each link to add the search to Alfred.</p>\n<ul>\n",
+    );
+    for (key, bang) in bangs {
+        let name = display_name(key, bang);
+        let link = format!(
+            "alfred://customsearch/{}/{}/utf8/noplus/{}",
+            urlencoding::encode(name),
+            urlencoding::encode(keyword(key)),
+            urlencoding::encode(&bang.url.replace("{searchTerms}", "{query}")),
+        );
+        html.push_str(&format!(
+            "  <li><a href=\"{}\">{}</a> <code>{}</code></li>\n",
+            escape_html(&link),
+            escape_html(name),
+            escape_html(keyword(key)),
+        ));
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+    html
+}
+
+// Export the bangs visible to the caller
+pub fn export(config: &Config, format: ExportFormat, include_private: bool) -> String {
+    let bangs = exported_bangs(config, include_private);
+    match format {
+        ExportFormat::Bookmarks => netscape_bookmarks(&bangs),
+        ExportFormat::Chrome => serde_json::to_string_pretty(&chrome_policy(&bangs)).unwrap_or_default(),
+        ExportFormat::Raycast => serde_json::to_string_pretty(&raycast_quicklinks(&bangs)).unwrap_or_default(),
+        ExportFormat::Alfred => alfred_links(&bangs),
+    }
+}
+
+// An OpenSearch description that searches one bang's site directly.
+// `self_url` is where the document itself is served.
+pub fn bang_opensearch(key: &str, bang: &BangDetails, self_url: &str) -> quick_xml::Result<String> {
+    let opensearch = OpenSearchConfig {
+        short_name: display_name(key, bang).to_string(),
+        description: bang
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Search {} ({})", display_name(key, bang), key)),
+        tags: bang.category.iter().chain(&bang.tags).cloned().collect(),
+        ..Default::default()
+    };
+    let urls = DocumentUrls {
+        search: bang.url.clone(),
+        suggest: None,
+        search_form: bang.home().unwrap_or_else(|| bang.url.clone()),
+        self_url: self_url.to_string(),
+    };
+    render_description(&opensearch, &urls)
+}
@@ -0,0 +1,82 @@
+use serde_yaml::Value;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// File formats a config can be written in. They all parse to the same
+// serde_yaml::Value, so includes, interpolation and merging behave the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownFormat(pub String);
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown config format '{}': use yaml, toml or json", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFormat {}
+
+impl FromStr for ConfigFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(UnknownFormat(name.to_string())),
+        }
+    }
+}
+
+impl ConfigFormat {
+    // The format a file's extension names, if it names one
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    pub fn parse(self, contents: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        Ok(match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)?,
+            ConfigFormat::Toml => toml::from_str(contents)?,
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+        })
+    }
+
+    pub fn render(self, value: &Value) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match self {
+            ConfigFormat::Yaml => serde_yaml::to_string(value)?,
+            // TOML has no null; an unset setting is simply left out
+            ConfigFormat::Toml => toml::to_string_pretty(&without_nulls(value.clone()))?,
+            ConfigFormat::Json => serde_json::to_string_pretty(value)? + "\n",
+        })
+    }
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Mapping(entries) => Value::Mapping(
+            entries
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Sequence(items) => Value::Sequence(items.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+// Translate a single config file between formats. The file is not merged or
+// interpolated, so includes and ${...} references carry over as written;
+// comments do not.
+pub fn convert(contents: &str, from: ConfigFormat, to: ConfigFormat) -> Result<String, Box<dyn std::error::Error>> {
+    to.render(&from.parse(contents)?)
+}
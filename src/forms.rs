@@ -0,0 +1,48 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::BangDetails;
+
+// The only script on the form page; its hash is allowed by the CSP
+pub const AUTO_SUBMIT_SCRIPT: &str = "document.forms[0].submit();";
+
+// A search to be sent to the target site as a POSTed form
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormPost {
+    pub action: String,
+    pub fields: Vec<(String, String)>,
+}
+
+// Fill the bang's action URL and form fields with the search term
+pub fn build_form(details: &BangDetails, search_term: &str) -> FormPost {
+    let action = details.url.replace("{searchTerms}", &urlencoding::encode(search_term));
+    let fields = details
+        .form
+        .iter()
+        .map(|(name, value)| (name.clone(), value.replace("{searchTerms}", search_term)))
+        .collect();
+    FormPost { action, fields }
+}
+
+// Only submit automatically for searches the user started themselves: from
+// the address bar ("none") or our own pages ("same-origin"). Anything else,
+// including a missing header, could be another site using us to forge a
+// POST, so the user has to press the button.
+pub fn should_auto_submit(sec_fetch_site: Option<&str>) -> bool {
+    matches!(sec_fetch_site, Some("none") | Some("same-origin"))
+}
+
+// Content-Security-Policy for the form page: no resources, only our own
+// script, and the form may only be sent to the target's origin
+pub fn content_security_policy(action: &str) -> String {
+    let script_hash = STANDARD.encode(Sha256::digest(AUTO_SUBMIT_SCRIPT.as_bytes()));
+    let form_action = Url::parse(action)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| "'none'".to_string());
+
+    format!(
+        "default-src 'none'; script-src 'sha256-{}'; form-action {}; frame-ancestors 'none'; base-uri 'none'",
+        script_hash, form_action
+    )
+}
@@ -0,0 +1,200 @@
+use regex::Regex;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::config::{validate_bang, BangDetails, Config, HttpMethod};
+
+// Where keyword searches are imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    // Firefox keyword bookmarks in places.sqlite
+    Firefox,
+    // Chrome search engines in the "Web Data" database
+    Chrome,
+    // A Netscape bookmark file with SHORTCUTURL attributes
+    Html,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownSource(pub String);
+
+impl fmt::Display for UnknownSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown import source '{}': use firefox, chrome or html", self.0)
+    }
+}
+
+impl std::error::Error for UnknownSource {}
+
+impl FromStr for ImportSource {
+    type Err = UnknownSource;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "firefox" => Ok(ImportSource::Firefox),
+            "chrome" => Ok(ImportSource::Chrome),
+            "html" => Ok(ImportSource::Html),
+            _ => Err(UnknownSource(name.to_string())),
+        }
+    }
+}
+
+impl ImportSource {
+    // Guess the source from the file names browsers use
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        match name.as_str() {
+            "places.sqlite" => Some(ImportSource::Firefox),
+            "web data" => Some(ImportSource::Chrome),
+            _ if name.ends_with(".html") || name.ends_with(".htm") => Some(ImportSource::Html),
+            _ => None,
+        }
+    }
+}
+
+// A keyword search found in a browser export
+#[derive(Debug, Clone)]
+pub struct ImportedBang {
+    // The browser keyword, e.g. "yt"
+    pub keyword: String,
+    pub bang: BangDetails,
+}
+
+// What importing would add to a config, and what it leaves out
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    pub bangs: BTreeMap<String, BangDetails>,
+    // Keys that already exist with a different URL; the existing bang is kept
+    pub conflicts: Vec<String>,
+    // Entries that are already there or cannot be used as bangs
+    pub skipped: Vec<String>,
+}
+
+// Browsers use %s for the search term; Firefox also has %S for the raw term
+fn search_template(url: &str) -> String {
+    url.replace("%s", "{searchTerms}").replace("%S", "{searchTerms}")
+}
+
+fn imported(keyword: &str, url: &str, title: Option<String>) -> ImportedBang {
+    ImportedBang {
+        keyword: keyword.to_string(),
+        bang: BangDetails {
+            url: search_template(url),
+            name: title.filter(|t| !t.is_empty()).unwrap_or_else(|| keyword.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+// Browser databases are opened read-only, so a running browser is not disturbed
+fn open_read_only(path: &Path) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+}
+
+// Keyword bookmarks from a Firefox profile's places.sqlite. Keywords with
+// POST data become form bangs.
+pub fn read_firefox(path: &Path) -> rusqlite::Result<Vec<ImportedBang>> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn.prepare(
+        "SELECT k.keyword, p.url, k.post_data,
+            (SELECT b.title FROM moz_bookmarks b WHERE b.fk = p.id AND b.title IS NOT NULL LIMIT 1)
+         FROM moz_keywords k JOIN moz_places p ON p.id = k.place_id
+         ORDER BY k.keyword",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let mut bang = imported(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?, row.get(3)?);
+        let post_data: Option<String> = row.get(2)?;
+        if let Some(post_data) = post_data.filter(|p| !p.is_empty()) {
+            bang.bang.method = HttpMethod::Post;
+            bang.bang.form = url::form_urlencoded::parse(post_data.as_bytes())
+                .map(|(name, value)| (name.into_owned(), search_template(&value)))
+                .collect();
+        }
+        Ok(bang)
+    })?;
+    rows.collect()
+}
+
+// Search engines from a Chrome profile's "Web Data" database. Chrome already
+// writes {searchTerms}; engines using its other placeholders are skipped
+// later because they are not valid URLs.
+pub fn read_chrome(path: &Path) -> rusqlite::Result<Vec<ImportedBang>> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn.prepare("SELECT keyword, url, short_name FROM keywords ORDER BY keyword")?;
+    let rows = stmt.query_map([], |row| Ok(imported(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let attribute = ATTRIBUTE.get_or_init(|| Regex::new(r#"(?i)\b([A-Z_]+)\s*=\s*"([^"]*)""#).unwrap());
+    attribute
+        .captures_iter(tag)
+        .find(|c| c[1].eq_ignore_ascii_case(name))
+        .map(|c| unescape_html(&c[2]))
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// Bookmarks with a SHORTCUTURL keyword from a Netscape bookmark file, as
+// exported by Firefox and other browsers
+pub fn parse_netscape_html(html: &str) -> Vec<ImportedBang> {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"(?is)<a\s([^>]*)>(.*?)</a>").unwrap());
+
+    link.captures_iter(html)
+        .filter_map(|c| {
+            let keyword = html_attribute(&c[1], "SHORTCUTURL").filter(|k| !k.is_empty())?;
+            let url = html_attribute(&c[1], "HREF")?;
+            Some(imported(&keyword, &url, Some(unescape_html(c[2].trim()))))
+        })
+        .collect()
+}
+
+pub fn read_import(path: &Path, source: ImportSource) -> Result<Vec<ImportedBang>, Box<dyn std::error::Error>> {
+    Ok(match source {
+        ImportSource::Firefox => read_firefox(path)?,
+        ImportSource::Chrome => read_chrome(path)?,
+        ImportSource::Html => parse_netscape_html(&std::fs::read_to_string(path)?),
+    })
+}
+
+// Work out which imported keywords can be added to a config as bangs.
+// Nothing existing is overwritten: a key that is already taken by a bang with
+// a different URL is reported as a conflict.
+pub fn plan_import(config: &Config, imported: Vec<ImportedBang>) -> ImportPlan {
+    let mut plan = ImportPlan::default();
+    for ImportedBang { keyword, bang } in imported {
+        let key = if keyword.starts_with('!') { keyword } else { format!("!{}", keyword) };
+        if let Err(e) = validate_bang(&key, &bang) {
+            plan.skipped.push(e.to_string());
+            continue;
+        }
+
+        let existing = config.bangs.get(&key).or_else(|| config.private_bangs.get(&key));
+        let existing = existing.map(|b| (b, b.source.as_deref().unwrap_or("the config")));
+        match existing.or_else(|| plan.bangs.get(&key).map(|b| (b, "this import"))) {
+            Some((other, _)) if other.url == bang.url => {
+                plan.skipped.push(format!("Bang '{}' already exists with the same URL", key));
+            }
+            Some((other, source)) => plan.conflicts.push(format!(
+                "Bang '{}' is already defined in {} as {}; not importing {}",
+                key, source, other.url, bang.url
+            )),
+            None => {
+                plan.bangs.insert(key, bang);
+            }
+        }
+    }
+    plan
+}
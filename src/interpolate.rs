@@ -0,0 +1,132 @@
+use serde_yaml::Value;
+use std::fmt;
+
+// A ${...} reference in a config string that could not be filled in. The
+// messages name the variable or file, never a value.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InterpolationError {
+    MissingVariable(String),
+    UnreadableSecret { path: String, error: String },
+    Unterminated(String),
+    // A reference in a bang from the admin API that the config files don't use
+    NotAllowed(String),
+}
+
+impl fmt::Display for InterpolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolationError::MissingVariable(name) => {
+                write!(
+                    f,
+                    "Environment variable '{}' is not set and has no default; set it, or write ${{{}:-default}}",
+                    name, name
+                )
+            }
+            InterpolationError::UnreadableSecret { path, error } => {
+                write!(f, "Cannot read secret file '{}': {}", path, error)
+            }
+            InterpolationError::Unterminated(text) => write!(f, "Unterminated '${{' in '{}'", text),
+            InterpolationError::NotAllowed(text) => {
+                write!(f, "'{}' must be written exactly like a ${{...}} value in the config files", text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterpolationError {}
+
+// Fill in one reference: "VAR", "VAR:-default", "file:/path" or
+// "file:/path:-default"
+fn lookup(reference: &str) -> Result<String, InterpolationError> {
+    let (name, default) = match reference.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference, None),
+    };
+
+    let value = match name.strip_prefix("file:") {
+        Some(path) => match std::fs::read_to_string(path) {
+            // Secret files usually end with a newline that is not part of the secret
+            Ok(contents) => Some(contents.trim_end_matches(['\n', '\r']).to_string()),
+            Err(_) if default.is_some() => None,
+            Err(e) => {
+                return Err(InterpolationError::UnreadableSecret {
+                    path: path.to_string(),
+                    error: e.to_string(),
+                })
+            }
+        },
+        // Like the shell, ":-" also replaces a variable that is set but empty
+        None => std::env::var(name).ok().filter(|value| default.is_none() || !value.is_empty()),
+    };
+
+    value
+        .or_else(|| default.map(str::to_string))
+        .ok_or_else(|| InterpolationError::MissingVariable(name.to_string()))
+}
+
+// Replace ${VAR}, ${VAR:-default} and ${file:/path} references in a string.
+// "$${" is a literal "${". Every value filled in is added to `secrets`, so
+// error messages can leave them out.
+pub fn interpolate(text: &str, secrets: &mut Vec<String>) -> Result<String, InterpolationError> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            result.push_str(&rest[..start - 1]);
+            result.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| InterpolationError::Unterminated(text.to_string()))?;
+
+        let value = lookup(&rest[start + 2..start + end])?;
+        if !value.is_empty() {
+            secrets.push(value.clone());
+        }
+        result.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+// Interpolate every string in a parsed config file
+pub fn interpolate_value(value: &mut Value, secrets: &mut Vec<String>) -> Result<(), InterpolationError> {
+    match value {
+        Value::String(text) if text.contains("${") => *text = interpolate(text, secrets)?,
+        Value::Sequence(items) => {
+            for item in items {
+                interpolate_value(item, secrets)?;
+            }
+        }
+        Value::Mapping(entries) => {
+            for (_, item) in entries.iter_mut() {
+                interpolate_value(item, secrets)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_value(&mut tagged.value, secrets)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+// Whether any string in a parsed value has a ${...} reference
+pub fn has_references(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains("${"),
+        Value::Sequence(items) => items.iter().any(has_references),
+        Value::Mapping(entries) => entries.values().any(has_references),
+        Value::Tagged(tagged) => has_references(&tagged.value),
+        _ => false,
+    }
+}
+
+// Hide interpolated values in a message that may quote config values
+pub fn redact(message: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .fold(message.to_string(), |message, secret| message.replace(secret.as_str(), "[redacted]"))
+}
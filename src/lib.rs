@@ -1,4 +1,28 @@
 pub mod bangs;
 pub mod models;
+#[cfg(feature = "server")]
 pub mod routes;
-pub mod config; 
\ No newline at end of file
+pub mod config;
+pub mod interpolate;
+pub mod formats;
+pub mod import;
+pub mod export;
+pub mod suggest;
+pub mod opensearch;
+pub mod auth;
+pub mod pages;
+pub mod storage;
+pub mod bookmarks;
+pub mod links;
+pub mod navigation;
+pub mod policy;
+pub mod rules;
+pub mod rewrite;
+pub mod forms;
+#[cfg(feature = "server")]
+pub mod state;
+pub mod resolver;
+pub mod admin;
+pub mod listing;
+pub mod deprecation;
+pub mod cases;
@@ -0,0 +1,219 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+use crate::auth::{Identity, OWNER_NAME};
+use crate::config::LinksConfig;
+use crate::storage::{now, Storage};
+
+// A named short link, shared by everyone who can search with a login
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Link {
+    pub name: String,
+    // Target URL, optionally with {1}..{9} and {rest} variables
+    pub url: String,
+    pub owner: String,
+    pub visits: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug)]
+pub enum LinkError {
+    InvalidName(String),
+    InvalidUrl(String),
+    NotFound(String),
+    AlreadyExists(String),
+    Forbidden(String),
+    Storage(rusqlite::Error),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::InvalidName(name) => write!(f, "Invalid link name '{}'", name),
+            LinkError::InvalidUrl(url) => write!(f, "Invalid link URL '{}'", url),
+            LinkError::NotFound(name) => write!(f, "Link '{}' not found", name),
+            LinkError::AlreadyExists(name) => write!(f, "Link '{}' already exists", name),
+            LinkError::Forbidden(name) => write!(f, "Link '{}' belongs to another user", name),
+            LinkError::Storage(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+impl From<rusqlite::Error> for LinkError {
+    fn from(e: rusqlite::Error) -> Self {
+        LinkError::Storage(e)
+    }
+}
+
+// Find the link name and arguments in a query, if it addresses a link.
+// "go/wiki rust" always does; "!go wiki rust" does when a link named "wiki"
+// exists, otherwise the !go bang itself handles the query.
+pub fn parse_link_query<'a>(
+    config: &LinksConfig,
+    query: &'a str,
+    bang: Option<&str>,
+    search_term: &'a str,
+) -> Option<(&'a str, &'a str)> {
+    let trimmed = query.trim();
+    if !config.prefix.is_empty() {
+        if let Some(rest) = trimmed.strip_prefix(config.prefix.as_str()) {
+            let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            return Some((name, args.trim()));
+        }
+    }
+
+    if bang == Some(config.bang.as_str()) {
+        let term = search_term.trim();
+        let (name, args) = term.split_once(char::is_whitespace).unwrap_or((term, ""));
+        if !name.is_empty() {
+            return Some((name, args.trim()));
+        }
+    }
+
+    None
+}
+
+// Fill {1}..{9} with the individual arguments and {rest} with all of them
+pub fn expand_link(url: &str, args: &str) -> String {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let mut expanded = url.replace("{rest}", &urlencoding::encode(args.trim()));
+    for i in 1..=9 {
+        let value = words.get(i - 1).copied().unwrap_or("");
+        expanded = expanded.replace(&format!("{{{}}}", i), &urlencoding::encode(value));
+    }
+    expanded
+}
+
+fn validate(name: &str, url: &str) -> Result<(), LinkError> {
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if !valid_name {
+        return Err(LinkError::InvalidName(name.to_string()));
+    }
+
+    // Check the URL with its variables filled in
+    match Url::parse(&expand_link(url, "x x x x x x x x x")) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        _ => Err(LinkError::InvalidUrl(url.to_string())),
+    }
+}
+
+// Only the link's owner and the auth_token holder may change a link
+fn check_owner(link: &Link, identity: &Identity) -> Result<(), LinkError> {
+    match identity.user_name() {
+        Some(OWNER_NAME) => Ok(()),
+        Some(user) if user == link.owner => Ok(()),
+        _ => Err(LinkError::Forbidden(link.name.clone())),
+    }
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Link> {
+    Ok(Link {
+        name: row.get(0)?,
+        url: row.get(1)?,
+        owner: row.get(2)?,
+        visits: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+const SELECT_LINK: &str = "SELECT name, url, owner, visits, created_at, updated_at FROM links";
+
+impl Storage {
+    pub fn create_link(&self, owner: &str, name: &str, url: &str) -> Result<Link, LinkError> {
+        validate(name, url)?;
+        if self.get_link(name)?.is_some() {
+            return Err(LinkError::AlreadyExists(name.to_string()));
+        }
+
+        let created_at = now();
+        self.conn().execute(
+            "INSERT INTO links (name, url, owner, visits, created_at, updated_at) VALUES (?1, ?2, ?3, 0, ?4, ?4)",
+            params![name, url, owner, created_at],
+        )?;
+        Ok(Link {
+            name: name.to_string(),
+            url: url.to_string(),
+            owner: owner.to_string(),
+            visits: 0,
+            created_at,
+            updated_at: created_at,
+        })
+    }
+
+    // Rename a link and/or change its URL
+    pub fn update_link(
+        &self,
+        identity: &Identity,
+        name: &str,
+        new_name: Option<&str>,
+        url: Option<&str>,
+    ) -> Result<Link, LinkError> {
+        let existing = self
+            .get_link(name)?
+            .ok_or_else(|| LinkError::NotFound(name.to_string()))?;
+        check_owner(&existing, identity)?;
+
+        let new_name = new_name.unwrap_or(&existing.name);
+        let url = url.unwrap_or(&existing.url);
+        validate(new_name, url)?;
+        if !new_name.eq_ignore_ascii_case(&existing.name) && self.get_link(new_name)?.is_some() {
+            return Err(LinkError::AlreadyExists(new_name.to_string()));
+        }
+
+        let updated_at = now();
+        self.conn().execute(
+            "UPDATE links SET name = ?2, url = ?3, updated_at = ?4 WHERE name = ?1",
+            params![existing.name, new_name, url, updated_at],
+        )?;
+        Ok(Link {
+            name: new_name.to_string(),
+            url: url.to_string(),
+            updated_at,
+            ..existing
+        })
+    }
+
+    pub fn delete_link(&self, identity: &Identity, name: &str) -> Result<(), LinkError> {
+        let existing = self
+            .get_link(name)?
+            .ok_or_else(|| LinkError::NotFound(name.to_string()))?;
+        check_owner(&existing, identity)?;
+
+        self.conn()
+            .execute("DELETE FROM links WHERE name = ?1", params![existing.name])?;
+        Ok(())
+    }
+
+    pub fn get_link(&self, name: &str) -> Result<Option<Link>, LinkError> {
+        let link = self
+            .conn()
+            .query_row(&format!("{} WHERE name = ?1", SELECT_LINK), params![name], from_row)
+            .optional()?;
+        Ok(link)
+    }
+
+    pub fn list_links(&self) -> Result<Vec<Link>, LinkError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY name", SELECT_LINK))?;
+        let links = stmt
+            .query_map([], from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(links)
+    }
+
+    // Look up a link for a redirect, counting the visit
+    pub fn visit_link(&self, name: &str) -> Result<Option<Link>, LinkError> {
+        self.conn()
+            .execute("UPDATE links SET visits = visits + 1 WHERE name = ?1", params![name])?;
+        self.get_link(name)
+    }
+}
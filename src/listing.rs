@@ -0,0 +1,168 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::config::{BangDetails, Config};
+use crate::models::BangsQuery;
+
+// Fields that can be selected with the `fields` parameter
+pub const BANG_FIELDS: [&str; 13] = [
+    "key",
+    "name",
+    "icon",
+    "url",
+    "category",
+    "visibility",
+    "description",
+    "tags",
+    "examples",
+    "deprecated",
+    "replaced_by",
+    "deprecated_until",
+    "source",
+];
+
+// Most bangs returned in one page, and the page size when no limit is given
+pub const MAX_LIMIT: usize = 500;
+
+// One bang in the list served at /bangs and printed by `bang_search list`
+#[derive(Debug, Serialize)]
+pub struct BangEntry {
+    pub key: String,
+    pub name: String,
+    pub icon: String,
+    pub url: String,
+    pub category: Option<String>,
+    pub visibility: &'static str,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub examples: Vec<String>,
+    pub deprecated: bool,
+    pub replaced_by: Option<String>,
+    pub deprecated_until: Option<String>,
+    // Config file the bang was defined in
+    pub source: Option<String>,
+}
+
+impl BangEntry {
+    pub fn new(key: &str, details: &BangDetails, private: bool) -> Self {
+        let visibility = if private { "private" } else { "public" };
+        BangEntry {
+            key: key.to_string(),
+            name: details.name.clone(),
+            icon: details.icon.clone(),
+            url: details.written.as_ref().map_or(&details.url, |written| &written.url).clone(),
+            category: details.category.clone(),
+            visibility,
+            description: details.description.clone(),
+            tags: details.tags.clone(),
+            examples: details.examples.clone(),
+            deprecated: details.is_deprecated(),
+            replaced_by: details.replaced_by.clone(),
+            deprecated_until: details.deprecated_until.clone(),
+            source: details.source.clone(),
+        }
+    }
+
+    fn matches(&self, q: &str) -> bool {
+        let q = q.to_lowercase();
+        std::iter::once(&self.key)
+            .chain([&self.name])
+            .chain(&self.description)
+            .chain(&self.tags)
+            .any(|text| text.to_lowercase().contains(&q))
+    }
+}
+
+// A page of the bang list
+#[derive(Debug, Serialize)]
+pub struct BangsList {
+    pub bangs: Vec<serde_json::Value>,
+    pub total: usize,
+    pub offset: usize,
+    pub next_offset: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownField(pub String);
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown field '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownField {}
+
+// Parse the comma-separated `fields` parameter, rejecting unknown names
+pub fn selected_fields(params: &BangsQuery) -> Result<Option<Vec<&str>>, UnknownField> {
+    let fields: Option<Vec<&str>> = params.fields.as_deref().map(|f| {
+        f.split(',').map(str::trim).filter(|f| !f.is_empty()).collect()
+    });
+    if let Some(unknown) = fields.iter().flatten().find(|f| !BANG_FIELDS.contains(f)) {
+        return Err(UnknownField(unknown.to_string()));
+    }
+    Ok(fields)
+}
+
+// Every bang visible to the caller, sorted by key for consistent output
+fn entries(config: &Config, is_authenticated: bool) -> Vec<BangEntry> {
+    let mut entries: Vec<BangEntry> = config
+        .bangs
+        .iter()
+        .map(|(key, details)| BangEntry::new(key, details, false))
+        .collect();
+    if is_authenticated {
+        entries.extend(config.private_bangs.iter().map(|(key, details)| BangEntry::new(key, details, true)));
+    }
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    entries
+}
+
+// Stable hash of the full listing that every /bangs response is cut from,
+// so it changes whenever any field a response can show does. It ends up in
+// ETags, and the listing leaves secrets such as auth_token out.
+pub fn bangs_fingerprint(config: &Config) -> String {
+    let value = serde_json::to_value(entries(config, true)).unwrap_or_default();
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    hex::encode(&digest[..16])
+}
+
+// Search, filter and paginate the bangs visible to the caller
+pub fn bang_list(config: &Config, params: &BangsQuery, is_authenticated: bool) -> Result<BangsList, UnknownField> {
+    let fields = selected_fields(params)?;
+    let mut entries = entries(config, is_authenticated);
+
+    // Apply the text search and the category and visibility filters
+    if let Some(q) = params.q.as_deref().filter(|q| !q.is_empty()) {
+        entries.retain(|b| b.matches(q));
+    }
+    if let Some(category) = params.category.as_deref().filter(|c| !c.is_empty()) {
+        entries.retain(|b| b.category.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(category)));
+    }
+    if let Some(visibility) = params.visibility.as_deref().filter(|v| !v.is_empty()) {
+        entries.retain(|b| b.visibility.eq_ignore_ascii_case(visibility));
+    }
+
+    // Paginate
+    let total = entries.len();
+    let offset = params.offset.unwrap_or(0).min(total);
+    // Pages always move forward and stay a manageable size
+    let limit = params.limit.unwrap_or(MAX_LIMIT).clamp(1, MAX_LIMIT);
+    let page: Vec<BangEntry> = entries.into_iter().skip(offset).take(limit).collect();
+    let next_offset = Some(offset + page.len()).filter(|next| *next < total);
+
+    // Only keep the selected fields
+    let bangs = page
+        .iter()
+        .map(|bang| {
+            let mut value = serde_json::to_value(bang).unwrap_or_default();
+            if let (Some(fields), Some(map)) = (&fields, value.as_object_mut()) {
+                map.retain(|k, _| fields.contains(&k.as_str()));
+            }
+            value
+        })
+        .collect();
+
+    Ok(BangsList { bangs, total, offset, next_offset })
+}
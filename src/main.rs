@@ -1,26 +1,357 @@
-use bang_search::{routes, config};
-mod models;
+use bang_search::{
+    admin::BangOverlay,
+    auth::{identity_for, Identity},
+    cases::{run_cases, CaseFile},
+    config::{self, config_schema, Config},
+    formats::{convert, ConfigFormat},
+    export::{export, ExportFormat},
+    import::{plan_import, read_import, ImportSource},
+    listing::bang_list,
+    models::BangsQuery,
+    resolver::{Action, BangResolver, Matched, Resolution},
+    routes,
+    state::AppState,
+};
+use clap::{Parser, Subcommand};
+use serde_json::json;
 
 use std::net::SocketAddr;
+use std::path::Path;
+use std::process::ExitCode;
 
-#[tokio::main]
-async fn main() {
-    // Load configuration from config.yml
-    let config_path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.yml".to_string());
-    let config = match config::load_config(&config_path).await {
+#[derive(Parser)]
+#[command(name = "bang_search", about = "Bang search server and command-line tools")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the HTTP server (the default)
+    Serve,
+    /// Show where a query goes, without running the server. Exits with 1 when
+    /// the query is blocked or invalid.
+    Resolve {
+        query: String,
+        /// Config files, separated like $PATH; defaults to $CONFIG_PATH or config.yml
+        #[arg(long)]
+        config: Option<String>,
+        /// Resolve as this user ("owner" for the auth_token holder)
+        #[arg(long)]
+        user: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run a YAML file of test cases through the resolver and report any
+    /// differences. Exits with 1 when a case fails.
+    Test {
+        cases: String,
+        /// Config files; defaults to the one named in the case file, then
+        /// $CONFIG_PATH or config.yml
+        #[arg(long)]
+        config: Option<String>,
+    },
+    /// Translate a config file between YAML, TOML and JSON. Includes and
+    /// ${...} references are kept as written; comments are not.
+    Convert {
+        input: String,
+        /// Output file; printed when unset
+        output: Option<String>,
+        /// Input format; defaults to the input's extension
+        #[arg(long)]
+        from: Option<ConfigFormat>,
+        /// Output format; defaults to the output's extension
+        #[arg(long)]
+        to: Option<ConfigFormat>,
+    },
+    /// Print the JSON Schema for config files
+    Schema,
+    /// Turn browser keyword searches into bangs, written as a config fragment
+    /// for config.d. Keys that already exist are reported and left alone;
+    /// exits with 1 when there are any.
+    Import {
+        /// Firefox places.sqlite, Chrome "Web Data" or a bookmarks .html file
+        file: String,
+        /// firefox, chrome or html; guessed from the file name when unset
+        #[arg(long)]
+        from: Option<ImportSource>,
+        /// Config to check for existing bangs; defaults to $CONFIG_PATH or config.yml
+        #[arg(long)]
+        config: Option<String>,
+        /// Import as private bangs
+        #[arg(long)]
+        private: bool,
+        /// Fragment file to write, in the format its extension names; printed as YAML when unset
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Export bangs like the /export endpoints, for machines without the server
+    Export {
+        /// bookmarks, chrome, raycast or alfred
+        format: ExportFormat,
+        /// Config file; defaults to $CONFIG_PATH or config.yml
+        #[arg(long)]
+        config: Option<String>,
+        /// Include the bangs this user can see ("owner" for the auth_token holder)
+        #[arg(long)]
+        user: Option<String>,
+        /// File to write; printed when unset
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// List bangs like the /bangs endpoint
+    List {
+        /// Config files, separated like $PATH; defaults to $CONFIG_PATH or config.yml
+        #[arg(long)]
+        config: Option<String>,
+        /// Include the bangs this user can see ("owner" for the auth_token holder)
+        #[arg(long)]
+        user: Option<String>,
+        /// Case-insensitive search over key, name, description and tags
+        #[arg(long)]
+        q: Option<String>,
+        #[arg(long)]
+        category: Option<String>,
+        /// public or private
+        #[arg(long)]
+        visibility: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(long)]
+        offset: Option<usize>,
+        /// Comma-separated fields to include with --json
+        #[arg(long)]
+        fields: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+// Config files named with --config or in $CONFIG_PATH, which must exist, or
+// ./config.yml if there is one
+fn config_path(path: Option<String>) -> Option<String> {
+    path.or_else(|| std::env::var("CONFIG_PATH").ok())
+        .or_else(|| Path::new("config.yml").exists().then(|| "config.yml".to_string()))
+}
+
+// Load the config files, or the default config when there are none
+async fn load_config_at(path: Option<&str>) -> Result<Config, Box<dyn std::error::Error>> {
+    match path {
+        Some(path) => config::load_config_as(path, config_format()?).await,
+        None => Ok(config::default_config()),
+    }
+}
+
+// Format set in $CONFIG_FORMAT, for config files whose extension doesn't say
+fn config_format() -> Result<Option<ConfigFormat>, Box<dyn std::error::Error>> {
+    match std::env::var("CONFIG_FORMAT") {
+        Ok(format) => Ok(Some(format.parse()?)),
+        Err(_) => Ok(None),
+    }
+}
+
+// Load a config for the command-line tools with the admin overlay applied, so
+// they see the same bangs as the running server
+async fn load_tool_config(path: Option<String>) -> Result<Config, Box<dyn std::error::Error>> {
+    let config = load_config_at(config_path(path).as_deref()).await?;
+    let overlay = BangOverlay::for_config(&config)?;
+    Ok(overlay.applied_to(&config)?)
+}
+
+fn tool_identity(config: &Config, user: Option<&str>) -> Result<Identity, String> {
+    match user {
+        Some(name) => identity_for(config, name).ok_or_else(|| format!("Unknown or revoked user '{}'", name)),
+        None => Ok(Identity::Anonymous),
+    }
+}
+
+fn describe_match(matched: &Matched) -> String {
+    match matched {
+        Matched::Link { name } => format!("go-link {}", name),
+        Matched::Bookmark { bang } => format!("bookmark command {}", bang),
+        Matched::Bang { key, private: false } => format!("bang {}", key),
+        Matched::Bang { key, private: true } => format!("private bang {}", key),
+        Matched::Rule { name } => format!("rule {}", name),
+        Matched::Navigation => "direct navigation".to_string(),
+        Matched::Default => "default engine".to_string(),
+    }
+}
+
+fn resolution_json(resolution: &Resolution) -> serde_json::Value {
+    let mut value = json!({
+        "query": resolution.query,
+        "term": resolution.term,
+        "matched": resolution.matched,
+        "url": resolution.url(),
+        "reason": resolution.reason,
+    });
+    match &resolution.action {
+        Action::Post(form) => {
+            value["method"] = json!("post");
+            value["form"] = json!(form.fields);
+        }
+        Action::Blocked { violation, .. } => value["error"] = json!(violation.to_string()),
+        Action::Invalid(message) => value["error"] = json!(message),
+        Action::Redirect(_) | Action::Bookmark => {}
+    }
+    if let Some(deprecation) = &resolution.deprecation {
+        value["deprecation"] = json!(deprecation.message());
+    }
+    value
+}
+
+fn print_resolution(resolution: &Resolution) {
+    println!("Query:   {}", resolution.query);
+    println!("Matched: {}", describe_match(&resolution.matched));
+    println!("Term:    {}", resolution.term);
+    match &resolution.action {
+        Action::Redirect(url) => println!("URL:     {}", url),
+        Action::Post(form) => {
+            println!("URL:     POST {}", form.action);
+            for (name, value) in &form.fields {
+                println!("         {}={}", name, value);
+            }
+        }
+        Action::Blocked { target, violation } => println!("Blocked: {} ({})", target, violation),
+        Action::Invalid(message) => println!("Error:   {}", message),
+        Action::Bookmark => println!("URL:     bookmark commands only run in the server"),
+    }
+    println!("Reason:  {}", resolution.reason);
+    if let Some(deprecation) = &resolution.deprecation {
+        println!("Notice:  {}", deprecation.message());
+    }
+}
+
+async fn resolve(query: String, config: Option<String>, user: Option<String>, json: bool) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let config = load_tool_config(config).await?;
+    let identity = tool_identity(&config, user.as_deref())?;
+
+    // No storage, so go-links are left out and nothing is counted as a visit
+    let resolution = BangResolver::new(&config).resolve(&query, &identity);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&resolution_json(&resolution))?);
+    } else {
+        print_resolution(&resolution);
+    }
+
+    match resolution.action {
+        Action::Blocked { .. } | Action::Invalid(_) => Ok(ExitCode::FAILURE),
+        _ => Ok(ExitCode::SUCCESS),
+    }
+}
+
+async fn test(cases: String, config: Option<String>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let file = CaseFile::load(&cases)?;
+    let config_path = config.or_else(|| file.config_path(&cases).map(|p| p.to_string_lossy().into_owned()));
+    let config = load_tool_config(config_path).await?;
+
+    let failures = run_cases(&config, &file.cases);
+    for failure in &failures {
+        println!("{}", failure);
+    }
+    println!("{} passed, {} failed", file.cases.len() - failures.len(), failures.len());
+
+    Ok(if failures.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn convert_file(input: String, output: Option<String>, from: Option<ConfigFormat>, to: Option<ConfigFormat>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let detect = |path: &str| ConfigFormat::from_extension(Path::new(path));
+    let from = from.or_else(|| detect(&input)).unwrap_or(ConfigFormat::Yaml);
+    let to = to
+        .or_else(|| output.as_deref().and_then(detect))
+        .ok_or("Give an output file with a .yml, .toml or .json extension, or --to")?;
+
+    let converted = convert(&std::fs::read_to_string(&input)?, from, to)?;
+    match output {
+        Some(path) => std::fs::write(path, converted)?,
+        None => print!("{}", converted),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn import(file: String, from: Option<ImportSource>, config: Option<String>, private: bool, output: Option<String>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let path = Path::new(&file);
+    let source = from
+        .or_else(|| ImportSource::detect(path))
+        .ok_or("Cannot tell what kind of export this is; use --from firefox, chrome or html")?;
+    let config = load_tool_config(config).await?;
+    let plan = plan_import(&config, read_import(path, source)?);
+
+    for message in plan.skipped.iter().chain(&plan.conflicts) {
+        eprintln!("{}", message);
+    }
+    eprintln!("{} imported, {} conflicts, {} skipped", plan.bangs.len(), plan.conflicts.len(), plan.skipped.len());
+
+    let section = if private { "private_bangs" } else { "bangs" };
+    let fragment = serde_yaml::to_value(std::collections::BTreeMap::from([(section, &plan.bangs)]))?;
+    match output {
+        Some(output) => {
+            let format = ConfigFormat::from_extension(Path::new(&output)).unwrap_or(ConfigFormat::Yaml);
+            std::fs::write(output, format.render(&fragment)?)?;
+        }
+        None => print!("{}", ConfigFormat::Yaml.render(&fragment)?),
+    }
+
+    Ok(if plan.conflicts.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+async fn export_bangs(format: ExportFormat, config: Option<String>, user: Option<String>, output: Option<String>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let config = load_tool_config(config).await?;
+    let identity = tool_identity(&config, user.as_deref())?;
+
+    let exported = export(&config, format, identity.is_authenticated());
+    match output {
+        Some(path) => std::fs::write(path, exported)?,
+        None => println!("{}", exported.trim_end()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn list(config: Option<String>, user: Option<String>, params: BangsQuery, json: bool) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let config = load_tool_config(config).await?;
+    let identity = tool_identity(&config, user.as_deref())?;
+
+    let list = bang_list(&config, &params, identity.is_authenticated())?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&list)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    for bang in &list.bangs {
+        let field = |name: &str| bang[name].as_str().unwrap_or_default().to_string();
+        println!("{:<12} {:<24} {:<8} {}", field("key"), field("name"), field("category"), field("url"));
+    }
+    println!("{} of {} bangs", list.bangs.len(), list.total);
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn serve() -> ExitCode {
+    // Load configuration from config.yml. A config that fails to load stops
+    // the server rather than falling back to defaults without an auth token.
+    let config_path = config_path(None);
+    let config = match load_config_at(config_path.as_deref()).await {
         Ok(cfg) => {
-            println!("Loaded configuration from {}", config_path);
+            println!("Loaded configuration from {}", config_path.as_deref().unwrap_or("the defaults"));
             cfg
         },
         Err(e) => {
             eprintln!("Failed to load configuration: {}", e);
-            eprintln!("Using default configuration");
-            config::default_config()
+            return ExitCode::FAILURE;
         }
     };
-    
+
+    // Open storage for bookmarks and other user data, and apply admin changes
+    let state = match AppState::new(config) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Failed to start: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     // Build our application with routes
-    let app = routes::create_router().with_state(config);
+    let app = routes::create_router().with_state(state);
 
     // Run the server
     let addr = SocketAddr::from(([0, 0, 0, 0], 9876));
@@ -29,4 +360,30 @@ async fn main() {
         .serve(app.into_make_service())
         .await
         .unwrap();
+    ExitCode::SUCCESS
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match Cli::parse().command.unwrap_or(Command::Serve) {
+        Command::Serve => return serve().await,
+        Command::Resolve { query, config, user, json } => resolve(query, config, user, json).await,
+        Command::Test { cases, config } => test(cases, config).await,
+        Command::Convert { input, output, from, to } => convert_file(input, output, from, to),
+        Command::Import { file, from, config, private, output } => import(file, from, config, private, output).await,
+        Command::Export { format, config, user, output } => export_bangs(format, config, user, output).await,
+        Command::Schema => {
+            println!("{}", serde_json::to_string_pretty(&config_schema()).unwrap_or_default());
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::List { config, user, q, category, visibility, limit, offset, fields, json } => {
+            let params = BangsQuery { q, category, visibility, limit, offset, fields, login: None };
+            list(config, user, params, json).await
+        }
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        ExitCode::FAILURE
+    })
 }
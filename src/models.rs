@@ -1,9 +1,101 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::BangDetails;
+
 // Query parameters struct
 #[derive(Debug, Deserialize, Serialize)]
 #[allow(dead_code)]  // Suppress the warning
 pub struct SearchQuery {
     pub q: Option<String>,
     pub login: Option<String>,
-} 
\ No newline at end of file
+}
+
+// Query parameters for the /bangs endpoint
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct BangsQuery {
+    // Case-insensitive text search over key, name, description and tags
+    pub q: Option<String>,
+    // The bang's category, e.g. "code"
+    pub category: Option<String>,
+    // "public" or "private"
+    pub visibility: Option<String>,
+    // Page size, from 1 to listing::MAX_LIMIT
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    // Comma-separated list of fields to include in each entry
+    pub fields: Option<String>,
+    pub login: Option<String>,
+}
+
+// Query parameters for the /export endpoints
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct ExportQuery {
+    // Include private bangs for this credential
+    pub login: Option<String>,
+}
+
+// Request body for creating or updating a bookmark
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct BookmarkInput {
+    pub name: Option<String>,
+    pub url: Option<String>,
+}
+
+// Request body for creating or updating a go-link
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct LinkInput {
+    pub name: Option<String>,
+    pub url: Option<String>,
+}
+
+// Request body for creating or updating a bang through the admin API
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct AdminBangInput {
+    // New name when updating, to rename the bang
+    pub name: Option<String>,
+    // Move the bang between public and private
+    pub private: Option<bool>,
+    pub bang: Option<BangDetails>,
+}
+
+// Query parameters for the /admin page
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct AdminPageQuery {
+    // Bang to load into the edit form
+    pub edit: Option<String>,
+    // Query used for the "try it" previews
+    pub sample: Option<String>,
+}
+
+// Fields of the add/edit form on the /admin page
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
+pub struct AdminBangForm {
+    // Name of the bang being edited; empty when adding one
+    pub original: String,
+    pub key: String,
+    pub name: String,
+    pub url: String,
+    pub icon: String,
+    pub home_url: String,
+    pub mode: String,
+    // Checkbox, present only when ticked
+    pub private: Option<String>,
+    pub sample: String,
+    // "save" or "test"
+    pub action: String,
+}
+
+// Form posted to /admin/login
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct AdminLoginForm {
+    pub token: String,
+}
+
+// Form posted to /admin/delete
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct AdminDeleteForm {
+    pub name: String,
+    #[serde(default)]
+    pub sample: String,
+}
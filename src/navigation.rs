@@ -0,0 +1,122 @@
+use std::fmt;
+use std::net::IpAddr;
+use url::{Host, Url};
+
+use crate::config::NavigationConfig;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NavigationError {
+    InvalidUrl(String),
+    SchemeNotAllowed(String),
+    HostNotAllowed(String),
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NavigationError::InvalidUrl(target) => write!(f, "'{}' is not a valid URL", target),
+            NavigationError::SchemeNotAllowed(scheme) => write!(f, "Navigation to '{}:' URLs is not allowed", scheme),
+            NavigationError::HostNotAllowed(host) => write!(f, "Navigation to '{}' is not allowed", host),
+        }
+    }
+}
+
+impl std::error::Error for NavigationError {}
+
+// Whether a host is one of the listed hosts or a subdomain of one
+pub fn host_matches(host: &str, list: &[String]) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    list.iter().any(|entry| {
+        let entry = entry.trim_end_matches('.').to_lowercase();
+        host == entry || host.ends_with(&format!(".{}", entry))
+    })
+}
+
+// Parse a term as a URL, adding https:// when the scheme is missing
+fn parse_target(term: &str) -> Option<Url> {
+    if term.contains("://") {
+        return Url::parse(term).ok();
+    }
+
+    // "localhost:8080" parses with "localhost" as its scheme, and
+    // "javascript:alert(1)" must not be taken at face value either, so
+    // anything without "://" is parsed as a host
+    Url::parse(&format!("https://{}", term)).ok()
+}
+
+// Resolve a typed URL or hostname to a navigation target allowed by the config
+pub fn navigation_target(term: &str, config: &NavigationConfig) -> Result<Url, NavigationError> {
+    let term = term.trim();
+    if !looks_like_url(term) {
+        return Err(NavigationError::InvalidUrl(term.to_string()));
+    }
+    let url = parse_target(term).ok_or_else(|| NavigationError::InvalidUrl(term.to_string()))?;
+
+    if !config.schemes.iter().any(|s| s.eq_ignore_ascii_case(url.scheme())) {
+        return Err(NavigationError::SchemeNotAllowed(url.scheme().to_string()));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| NavigationError::InvalidUrl(term.to_string()))?
+        .to_string();
+    if host_matches(&host, &config.deny_hosts)
+        || (!config.allow_hosts.is_empty() && !host_matches(&host, &config.allow_hosts))
+    {
+        return Err(NavigationError::HostNotAllowed(host));
+    }
+
+    Ok(url)
+}
+
+// Generic top-level domains accepted without a scheme. Any other two letters
+// are accepted as a country code, unless they are a file extension below.
+const GENERIC_TLDS: &[&str] = &[
+    "app", "biz", "blog", "cloud", "com", "dev", "edu", "gov", "info", "int", "mil", "museum", "name", "net",
+    "org", "page", "pro", "shop", "site", "tech", "xyz",
+];
+
+// Two-letter file extensions that are not country codes, so "node.js" is a
+// file name. Ones that are, like .rs or .pl, stay domains: crates.rs is a
+// site, and longer extensions like .json are never taken for a TLD.
+const FILE_EXTENSIONS: &[&str] = &["cs", "gz", "hs", "js", "kt", "rb", "ts"];
+
+fn is_known_tld(tld: &str) -> bool {
+    let tld = tld.to_ascii_lowercase();
+    if FILE_EXTENSIONS.contains(&tld.as_str()) {
+        return false;
+    }
+    GENERIC_TLDS.contains(&tld.as_str()) || (tld.len() == 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+// Whether a bare query is clearly a URL or domain rather than search words.
+// Without a scheme the domain needs a known top-level domain, so file names
+// like "package.json" stay searches.
+pub fn looks_like_url(query: &str) -> bool {
+    let query = query.trim();
+    if query.is_empty() || query.chars().any(char::is_whitespace) {
+        return false;
+    }
+    if query.contains("://") {
+        return Url::parse(query).is_ok();
+    }
+
+    let Some(url) = parse_target(query) else {
+        return false;
+    };
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            if domain == "localhost" {
+                return true;
+            }
+            match domain.rsplit_once('.') {
+                Some((name, tld)) => !name.is_empty() && is_known_tld(tld),
+                None => false,
+            }
+        }
+        // Only dotted IPv4 addresses, so plain numbers stay searches
+        Some(Host::Ipv4(_)) => query.split(['/', ':']).next().and_then(|h| h.parse::<IpAddr>().ok()).is_some(),
+        Some(Host::Ipv6(_)) => true,
+        None => false,
+    }
+}
@@ -0,0 +1,121 @@
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use std::io::Cursor;
+
+use crate::config::OpenSearchConfig;
+
+const OPENSEARCH_NS: &str = "http://a9.com/-/spec/opensearch/1.1/";
+const MOZ_NS: &str = "http://www.mozilla.org/2006/browser/search/";
+
+// Absolute URLs advertised by a description document
+#[derive(Debug, Clone)]
+pub struct DocumentUrls {
+    pub search: String,
+    // Left out of documents for sites we have no completions for
+    pub suggest: Option<String>,
+    pub search_form: String,
+    pub self_url: String,
+}
+
+impl DocumentUrls {
+    // URLs for the shared, unauthenticated document served from host_url
+    pub fn for_host(host_url: &str) -> Self {
+        let host_url = host_url.trim_end_matches('/');
+        DocumentUrls {
+            search: format!("{}/search?q={{searchTerms}}", host_url),
+            suggest: Some(format!("{}/suggest?q={{searchTerms}}", host_url)),
+            search_form: format!("{}/", host_url),
+            self_url: format!("{}/opensearch.xml", host_url),
+        }
+    }
+}
+
+// Guess the MIME type of the icon from its file extension
+fn icon_type(icon: &str) -> &'static str {
+    let path = icon.split(['?', '#']).next().unwrap_or(icon).to_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".svg") {
+        "image/svg+xml"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+        "image/jpeg"
+    } else {
+        "image/x-icon"
+    }
+}
+
+fn write_text(writer: &mut Writer<Cursor<Vec<u8>>>, name: &str, text: &str) -> quick_xml::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(text))?;
+    Ok(())
+}
+
+fn write_url(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    mime: &str,
+    rel: Option<&str>,
+    template: &str,
+) -> quick_xml::Result<()> {
+    let mut element = writer
+        .create_element("Url")
+        .with_attribute(("type", mime))
+        .with_attribute(("method", "get"));
+    if let Some(rel) = rel {
+        element = element.with_attribute(("rel", rel));
+    }
+    element.with_attribute(("template", template)).write_empty()?;
+    Ok(())
+}
+
+// Render an OpenSearch description document, escaping every configured value
+pub fn render_description(opensearch: &OpenSearchConfig, urls: &DocumentUrls) -> quick_xml::Result<String> {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    writer
+        .create_element("OpenSearchDescription")
+        .with_attribute(("xmlns", OPENSEARCH_NS))
+        .with_attribute(("xmlns:moz", MOZ_NS))
+        .write_inner_content(|writer| {
+            write_text(writer, "ShortName", &opensearch.short_name)?;
+            write_text(writer, "Description", &opensearch.description)?;
+
+            write_url(writer, "text/html", None, &urls.search)?;
+            if let Some(suggest) = &urls.suggest {
+                write_url(writer, "application/x-suggestions+json", None, suggest)?;
+            }
+            write_url(writer, "application/opensearchdescription+xml", Some("self"), &urls.self_url)?;
+
+            if let Some(contact) = &opensearch.contact {
+                write_text(writer, "Contact", contact)?;
+            }
+            if !opensearch.tags.is_empty() {
+                write_text(writer, "Tags", &opensearch.tags.join(" "))?;
+            }
+            if let Some(long_name) = &opensearch.long_name {
+                write_text(writer, "LongName", long_name)?;
+            }
+            if let Some(icon) = &opensearch.icon {
+                writer
+                    .create_element("Image")
+                    .with_attribute(("width", "16"))
+                    .with_attribute(("height", "16"))
+                    .with_attribute(("type", icon_type(icon)))
+                    .write_text_content(BytesText::new(icon))?;
+            }
+            if let Some(language) = &opensearch.language {
+                write_text(writer, "Language", language)?;
+            }
+            write_text(writer, "InputEncoding", &opensearch.input_encoding)?;
+            write_text(writer, "SearchForm", &urls.search_form)?;
+            write_text(writer, "moz:SearchForm", &urls.search_form)?;
+            Ok::<(), quick_xml::Error>(())
+        })?;
+
+    let xml = String::from_utf8(writer.into_inner().into_inner())
+        .expect("quick-xml writes valid UTF-8");
+    Ok(xml)
+}
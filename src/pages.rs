@@ -0,0 +1,330 @@
+use crate::admin::{list_bangs, preview_target, AdminBang};
+use crate::bookmarks::Bookmark;
+use crate::config::{BangDetails, Config, HttpMethod};
+use crate::deprecation::Deprecation;
+use crate::forms::{FormPost, AUTO_SUBMIT_SCRIPT};
+use crate::models::AdminBangForm;
+use crate::policy::PolicyViolation;
+
+// Templates are embedded so the binary needs no files next to it
+const INDEX_TEMPLATE: &str = include_str!("../templates/index.html");
+const DIRECTORY_TEMPLATE: &str = include_str!("../templates/directory.html");
+const BOOKMARKS_TEMPLATE: &str = include_str!("../templates/bookmarks.html");
+const BLOCKED_TEMPLATE: &str = include_str!("../templates/blocked.html");
+const DEPRECATED_TEMPLATE: &str = include_str!("../templates/deprecated.html");
+const POST_FORM_TEMPLATE: &str = include_str!("../templates/post_form.html");
+const ADMIN_TEMPLATE: &str = include_str!("../templates/admin.html");
+const ADMIN_LOGIN_TEMPLATE: &str = include_str!("../templates/admin_login.html");
+
+// Escape text for use in HTML content and quoted attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Fill {{name}} placeholders; values must already be escaped
+fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+
+    // Single pass, so placeholders inside substituted values are left alone
+    while let Some(start) = rest.find("{{") {
+        page.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = &after[..end];
+                match values.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => page.push_str(value),
+                    None => page.push_str(&rest[start..start + end + 4]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                page.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    page.push_str(rest);
+    page
+}
+
+// Append the login parameter to a local link so authenticated users stay signed in
+fn with_login(path: &str, login: Option<&str>) -> String {
+    match login {
+        Some(login) => format!("{}?login={}", path, urlencoding::encode(login)),
+        None => path.to_string(),
+    }
+}
+
+// Favicon of the site a bang points to, when the template has a fixed host
+fn favicon_url(details: &BangDetails) -> Option<String> {
+    Some(format!("{}favicon.ico", details.origin()?))
+}
+
+// Landing page with a search box
+pub fn index_page(config: &Config, login: Option<&str>) -> String {
+    let login_field = match login {
+        Some(login) => format!(r#"<input type="hidden" name="login" value="{}">"#, escape_html(login)),
+        None => String::new(),
+    };
+
+    render(INDEX_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("description", escape_html(&config.opensearch.description)),
+        ("opensearch_url", "/opensearch.xml".to_string()),
+        ("directory_url", escape_html(&with_login("/directory", login))),
+        ("login_field", login_field),
+    ])
+}
+
+// Directory of every bang visible to the caller
+pub fn directory_page(config: &Config, is_authenticated: bool, login: Option<&str>) -> String {
+    // Bangs without a category are grouped by visibility
+    let category = |details: &'_ BangDetails, visibility: &'static str| -> String {
+        details.category.clone().unwrap_or_else(|| visibility.to_string())
+    };
+    let mut entries: Vec<(&String, &BangDetails, String)> = config
+        .bangs
+        .iter()
+        .map(|(key, details)| (key, details, category(details, "Public")))
+        .collect();
+    if is_authenticated {
+        entries.extend(config.private_bangs.iter().map(|(key, details)| (key, details, category(details, "Private"))));
+    }
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut categories: Vec<&str> = entries.iter().map(|(_, _, category)| category.as_str()).collect();
+    categories.sort();
+    categories.dedup();
+    let category_options = categories
+        .iter()
+        .map(|category| format!(r#"<option value="{0}">{0}</option>"#, escape_html(category)))
+        .collect::<Vec<_>>()
+        .join("\n      ");
+
+    let rows = entries
+        .iter()
+        .map(|(key, details, category)| {
+            // Shown as written, so hosts and keys filled in from ${...} stay private
+            let details = details.as_written();
+            let icon = match favicon_url(&details) {
+                Some(src) => format!(
+                    r#"<img src="{}" alt="{}" loading="lazy">"#,
+                    escape_html(&src),
+                    escape_html(&details.icon)
+                ),
+                None => String::new(),
+            };
+            format!(
+                concat!(
+                    r#"<tr data-key="{key_lower}" data-name="{name_lower}" data-category="{category}">"#,
+                    r#"<td>{icon}</td><td><code>{key}</code></td><td>{name}</td><td>{category}</td>"#,
+                    r#"<td class="url">{url}</td>"#,
+                    r#"<td><button class="copy" data-bang="{key}">Copy</button></td></tr>"#
+                ),
+                key_lower = escape_html(&key.to_lowercase()),
+                name_lower = escape_html(&details.name.to_lowercase()),
+                category = escape_html(category),
+                icon = icon,
+                key = escape_html(key),
+                name = escape_html(&details.name),
+                url = match &details.replaced_by {
+                    Some(replacement) => format!("Replaced by {}", escape_html(replacement)),
+                    None => escape_html(&details.url),
+                },
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n      ");
+
+    render(DIRECTORY_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("home_url", escape_html(&with_login("/", login))),
+        ("opensearch_url", "/opensearch.xml".to_string()),
+        ("category_options", category_options),
+        ("count", entries.len().to_string()),
+        ("rows", rows),
+    ])
+}
+
+// Result page for bookmark commands, optionally listing bookmarks
+pub fn bookmarks_page(config: &Config, message: &str, bookmarks: &[Bookmark]) -> String {
+    let items = bookmarks
+        .iter()
+        .map(|bookmark| {
+            format!(
+                r#"<li><a href="{url}">{name}</a> <span class="url">{url}</span></li>"#,
+                url = escape_html(&bookmark.url),
+                name = escape_html(&bookmark.name),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    render(BOOKMARKS_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("message", escape_html(message)),
+        ("items", items),
+    ])
+}
+
+// Warning shown instead of redirecting to a URL the policy does not allow
+pub fn blocked_page(config: &Config, target: &str, violation: &PolicyViolation) -> String {
+    render(BLOCKED_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("reason", escape_html(&violation.to_string())),
+        ("target", escape_html(target)),
+    ])
+}
+
+// Notice about a deprecated or retired bang, with a link to carry on when
+// there is somewhere to go
+pub fn deprecation_page(config: &Config, deprecation: &Deprecation, next: Option<&str>) -> String {
+    let heading = if deprecation.retired { "Bang retired" } else { "Bang deprecated" };
+    let next = match next {
+        Some(url) => format!(r#"<p><a href="{}">Continue with your search</a></p>"#, escape_html(url)),
+        None => String::new(),
+    };
+    render(DEPRECATED_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("heading", heading.to_string()),
+        ("message", escape_html(&deprecation.message())),
+        ("next", next),
+    ])
+}
+
+// Page that POSTs a search to the target, submitting itself when allowed
+pub fn post_form_page(config: &Config, name: &str, form: &FormPost, auto_submit: bool) -> String {
+    let fields = form
+        .fields
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<input type="hidden" name="{}" value="{}">"#,
+                escape_html(name),
+                escape_html(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    let (prompt, script) = if auto_submit {
+        ("Sending your search...", format!("<script>{}</script>", AUTO_SUBMIT_SCRIPT))
+    } else {
+        ("Press the button to send your search.", String::new())
+    };
+
+    render(POST_FORM_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("name", escape_html(name)),
+        ("action", escape_html(&form.action)),
+        ("fields", fields),
+        ("prompt", prompt.to_string()),
+        ("script", script),
+    ])
+}
+
+// Login form for the admin interface
+pub fn admin_login_page(config: &Config, message: &str) -> String {
+    render(ADMIN_LOGIN_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("message", escape_html(message)),
+    ])
+}
+
+// Where a bang sends the sample query, or why it would be blocked
+fn preview_html(bang: &BangDetails, preview: &Result<String, String>) -> String {
+    match preview {
+        Ok(target) => {
+            let method = if bang.method == HttpMethod::Post { "POST " } else { "" };
+            format!(r#"{}<a href="{1}" rel="noreferrer">{1}</a>"#, method, escape_html(target))
+        }
+        Err(reason) => format!(r#"<span class="blocked">Blocked: {}</span>"#, escape_html(reason)),
+    }
+}
+
+// Table rows for the admin page, previewing each bang with the sample query
+fn admin_rows(config: &Config, bangs: &[AdminBang], sample: &str) -> String {
+    let sample_param = urlencoding::encode(sample);
+    bangs
+        .iter()
+        .map(|entry| {
+            let preview = preview_target(config, &entry.bang, sample);
+            format!(
+                concat!(
+                    r#"<tr><td><code>{key}</code></td><td>{name}</td><td class="url">{url}</td>"#,
+                    r#"<td class="preview">{preview}</td>"#,
+                    r#"<td><a href="/admin?edit={key_param}&amp;sample={sample_param}">Edit</a> "#,
+                    r#"<form method="post" action="/admin/delete">"#,
+                    r#"<input type="hidden" name="name" value="{key}">"#,
+                    r#"<input type="hidden" name="sample" value="{sample}">"#,
+                    r#"<button type="submit">Delete</button></form></td></tr>"#
+                ),
+                key = escape_html(&entry.name),
+                name = escape_html(&entry.bang.name),
+                url = escape_html(&entry.bang.url),
+                preview = preview_html(&entry.bang, &preview),
+                key_param = escape_html(&urlencoding::encode(&entry.name)),
+                sample_param = escape_html(&sample_param),
+                sample = escape_html(sample),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n      ")
+}
+
+// Bang management page with an add/edit form and a "try it" preview per bang
+pub fn admin_page(config: &Config, message: &str, form: &AdminBangForm, form_preview: Option<(&BangDetails, Result<String, String>)>) -> String {
+    let sample_param = urlencoding::encode(&form.sample).into_owned();
+    let (public, private): (Vec<AdminBang>, Vec<AdminBang>) =
+        list_bangs(config).into_iter().partition(|bang| !bang.private);
+
+    let mode_options = ["search", "navigate"]
+        .iter()
+        .map(|mode| {
+            let selected = if form.mode == *mode { " selected" } else { "" };
+            format!(r#"<option value="{0}"{1}>{0}</option>"#, mode, selected)
+        })
+        .collect::<String>();
+
+    let form_title = if form.original.is_empty() {
+        "Add a bang".to_string()
+    } else {
+        format!("Edit {}", form.original)
+    };
+    let form_preview = match form_preview {
+        Some((bang, preview)) => format!("Try it: {}", preview_html(bang, &preview)),
+        None => String::new(),
+    };
+
+    render(ADMIN_TEMPLATE, &[
+        ("title", escape_html(&config.opensearch.short_name)),
+        ("message", escape_html(message)),
+        ("form_title", escape_html(&form_title)),
+        ("original", escape_html(&form.original)),
+        ("key", escape_html(&form.key)),
+        ("name", escape_html(&form.name)),
+        ("url", escape_html(&form.url)),
+        ("icon", escape_html(&form.icon)),
+        ("home_url", escape_html(&form.home_url)),
+        ("mode_options", mode_options),
+        ("private_checked", if form.private.is_some() { " checked".to_string() } else { String::new() }),
+        ("sample", escape_html(&form.sample)),
+        ("sample_param", escape_html(&sample_param)),
+        ("form_preview", form_preview),
+        ("public_rows", admin_rows(config, &public, &form.sample)),
+        ("private_rows", admin_rows(config, &private, &form.sample)),
+    ])
+}
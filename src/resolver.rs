@@ -0,0 +1,245 @@
+use serde::Serialize;
+
+use crate::auth::Identity;
+use crate::bangs::extract_bang;
+use crate::bookmarks::parse_command;
+use crate::config::{BangDetails, BangMode, Config, HttpMethod};
+use crate::deprecation::Deprecation;
+use crate::forms::{build_form, FormPost};
+use crate::links::{expand_link, parse_link_query};
+use crate::navigation::{looks_like_url, navigation_target};
+use crate::policy::{PolicyViolation, RedirectPolicy};
+use crate::rewrite::rewrite_query;
+use crate::rules::match_rule;
+use crate::storage::Storage;
+
+// What handled a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Matched {
+    Link { name: String },
+    // A bookmark command such as "!ba name url"
+    Bookmark { bang: String },
+    Bang { key: String, private: bool },
+    Rule { name: String },
+    // A query that is just a URL, with auto_navigate on
+    Navigation,
+    Default,
+}
+
+// What should happen to a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Redirect(String),
+    // Submit a form to a site that only accepts POSTed searches
+    Post(FormPost),
+    // The target is not allowed by the redirect policy
+    Blocked { target: String, violation: PolicyViolation },
+    // A navigate-mode bang was given something that cannot be navigated to
+    Invalid(String),
+    // Run a bookmark command, which needs the caller's storage
+    Bookmark,
+}
+
+// The outcome of resolving a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    // The query after rewrites
+    pub query: String,
+    // The query without its bang
+    pub term: String,
+    pub matched: Matched,
+    pub action: Action,
+    // Why the query went where it did, for logs and debugging
+    pub reason: String,
+    // Set when the query used a deprecated bang
+    pub deprecation: Option<Deprecation>,
+    // A lookup that failed along the way, such as a storage error, for the
+    // caller to log
+    pub error: Option<String>,
+}
+
+impl Resolution {
+    // Where the search ends up: the redirect or form target, if it is allowed
+    pub fn url(&self) -> Option<&str> {
+        match &self.action {
+            Action::Redirect(url) => Some(url),
+            Action::Post(form) => Some(&form.action),
+            _ => None,
+        }
+    }
+}
+
+// Turns queries into redirects without depending on any web framework, so
+// the same rules apply in the server, the CLI and other services
+pub struct BangResolver<'a> {
+    config: &'a Config,
+    storage: Option<&'a Storage>,
+}
+
+impl<'a> BangResolver<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        BangResolver { config, storage: None }
+    }
+
+    // Also resolve go-links, counting each visit in storage
+    pub fn with_storage(mut self, storage: &'a Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    // Find a bang visible to the caller; public bangs win over private ones.
+    // The flag says whether the bang is private.
+    pub fn lookup(&self, key: &str, identity: &Identity) -> Option<(&'a BangDetails, bool)> {
+        if let Some(details) = self.config.bangs.get(key) {
+            return Some((details, false));
+        }
+        if identity.is_authenticated() {
+            return self.config.private_bangs.get(key).map(|details| (details, true));
+        }
+        None
+    }
+
+    // Allow the target if the bang's policy (or the global one) permits it
+    fn checked(&self, policy: Option<&RedirectPolicy>, target: String) -> Action {
+        let policy = policy.unwrap_or(&self.config.redirect_policy);
+        match policy.check(&target) {
+            Ok(_) => Action::Redirect(target),
+            Err(violation) => Action::Blocked { target, violation },
+        }
+    }
+
+    // Where a bang points for a search term, before the redirect policy is
+    // checked. For POST bangs this is the form's target.
+    pub fn bang_target(&self, details: &BangDetails, term: &str) -> Result<String, String> {
+        // A bare bang goes to the site's home page rather than an empty search
+        if term.trim().is_empty() {
+            if let Some(home) = details.home() {
+                return Ok(home);
+            }
+        }
+
+        match details.mode {
+            BangMode::Search => Ok(details.url.replace("{searchTerms}", &urlencoding::encode(term))),
+            BangMode::Navigate => navigation_target(term, &self.config.navigation)
+                .map(|url| url.to_string())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    // Where a bang sends a search term
+    pub fn resolve_bang(&self, details: &BangDetails, term: &str) -> Action {
+        let target = match self.bang_target(details, term) {
+            Ok(target) => target,
+            Err(message) => return Action::Invalid(message),
+        };
+        let posted = details.mode == BangMode::Search
+            && details.method == HttpMethod::Post
+            && !(term.trim().is_empty() && details.home().is_some());
+
+        // The form target gets the same checks as a redirect
+        match self.checked(details.policy.as_ref(), target) {
+            Action::Redirect(_) if posted => Action::Post(build_form(details, term)),
+            action => action,
+        }
+    }
+
+    // Resolve a query in order: go-links, bookmark commands, public then
+    // private bangs, rules, direct navigation and finally the default engine
+    pub fn resolve(&self, query: &str, identity: &Identity) -> Resolution {
+        let query = rewrite_query(&self.config.rewrites, query);
+        let (bang, term) = extract_bang(&query);
+
+        // Go-links come before bangs: "go/name" always resolves a link, and
+        // "!go name" does when the link exists, otherwise the !go bang applies
+        let (mut link, mut error) = (None, None);
+        if let Some(storage) = self.storage.filter(|_| identity.is_authenticated()) {
+            if let Some((name, args)) = parse_link_query(&self.config.links, &query, bang, term) {
+                match storage.visit_link(name) {
+                    Ok(found) => link = found.map(|found| (found, args)),
+                    // The search carries on without the link
+                    Err(e) => error = Some(format!("Failed to look up link '{}': {}", name, e)),
+                }
+            }
+        }
+
+        let resolution = |matched, action, reason: String| Resolution {
+            query: query.clone(),
+            term: term.to_string(),
+            matched,
+            action,
+            reason,
+            deprecation: None,
+            error: error.clone(),
+        };
+
+        if let Some((link, args)) = link {
+            let action = self.checked(None, expand_link(&link.url, args));
+            let reason = format!("go-link '{}'", link.name);
+            return resolution(Matched::Link { name: link.name }, action, reason);
+        }
+
+        if let Some(key) = bang {
+            // Bookmark commands take precedence for authenticated users
+            if identity.user_name().is_some() && parse_command(key, term).is_some() {
+                let matched = Matched::Bookmark { bang: key.to_string() };
+                return resolution(matched, Action::Bookmark, format!("bookmark command {}", key));
+            }
+
+            if let Some((details, private)) = self.lookup(key, identity) {
+                let visibility = if private { "private" } else { "public" };
+                let deprecation = Deprecation::for_bang(key, details);
+                let mut matched = Matched::Bang { key: key.to_string(), private };
+                let (action, reason) = match &deprecation {
+                    Some(deprecation) if deprecation.retired => {
+                        (Action::Invalid(deprecation.message()), format!("retired {} bang {}", visibility, key))
+                    }
+                    // Old keys keep working by going through their replacement
+                    Some(Deprecation { replaced_by: Some(replacement), .. }) => {
+                        match self.lookup(replacement, identity) {
+                            Some((details, private)) => {
+                                matched = Matched::Bang { key: replacement.clone(), private };
+                                let reason = format!("{} bang {}, replaced by {}", visibility, key, replacement);
+                                (self.resolve_bang(details, term), reason)
+                            }
+                            None => {
+                                let message = format!("{} was replaced by {}, which needs a login", key, replacement);
+                                (Action::Invalid(message), format!("replacement for {} not visible", key))
+                            }
+                        }
+                    }
+                    _ => (self.resolve_bang(details, term), format!("{} bang {}", visibility, key)),
+                };
+                return Resolution {
+                    deprecation,
+                    ..resolution(matched, action, reason)
+                };
+            }
+        }
+
+        // Pattern rules route well-known identifiers without a bang
+        if let Some((rule, url)) = match_rule(&self.config.rules, &query, identity.is_authenticated()) {
+            let matched = Matched::Rule { name: rule.name.clone() };
+            return resolution(matched, self.checked(None, url), format!("rule '{}'", rule.name));
+        }
+
+        // Queries that are just a URL or domain go straight there when allowed
+        if bang.is_none() && self.config.navigation.auto_navigate && looks_like_url(&query) {
+            if let Ok(url) = navigation_target(&query, &self.config.navigation) {
+                let reason = "query is a URL".to_string();
+                return resolution(Matched::Navigation, self.checked(None, url.to_string()), reason);
+            }
+        }
+
+        let url = self
+            .config
+            .default_engine
+            .url
+            .replace("{searchTerms}", &urlencoding::encode(&query));
+        let reason = match bang {
+            Some(key) => format!("bang '{}' not found, using the default engine", key),
+            None => "no bang or rule matched, using the default engine".to_string(),
+        };
+        resolution(Matched::Default, self.checked(None, url), reason)
+    }
+}
@@ -0,0 +1,123 @@
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::rules::RulePattern;
+
+// What a rewrite step does to the query
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RewriteKind {
+    // Regex replace; the replacement can use $1 or ${name}
+    Replace {
+        pattern: RulePattern,
+        #[serde(default)]
+        replacement: String,
+    },
+    // Swap a leading string, e.g. "mdn " -> "!mdn "
+    Prefix { from: String, to: String },
+    // Swap a trailing string, e.g. " on yt" -> " !yt"
+    Suffix { from: String, to: String },
+    // Replace whole words, e.g. k8s -> kubernetes
+    Synonyms { words: HashMap<String, String> },
+}
+
+// One step of the rewrite pipeline
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct RewriteStep {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: RewriteKind,
+}
+
+// Output of a single step, for the debug endpoint
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RewriteTrace {
+    pub step: String,
+    pub output: String,
+    pub changed: bool,
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &text[prefix.len()..])
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let start = text.len().checked_sub(suffix.len())?;
+    let tail = text.get(start..)?;
+    tail.eq_ignore_ascii_case(suffix).then(|| &text[..start])
+}
+
+impl RewriteStep {
+    // Label used in traces: the configured name, or the step type
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match self.kind {
+            RewriteKind::Replace { .. } => "replace",
+            RewriteKind::Prefix { .. } => "prefix",
+            RewriteKind::Suffix { .. } => "suffix",
+            RewriteKind::Synonyms { .. } => "synonyms",
+        }
+        .to_string()
+    }
+
+    pub fn apply(&self, query: &str) -> String {
+        match &self.kind {
+            RewriteKind::Replace { pattern, replacement } => {
+                pattern.0.replace_all(query, replacement.as_str()).into_owned()
+            }
+            RewriteKind::Prefix { from, to } => match strip_prefix_ignore_case(query, from) {
+                Some(rest) if !from.is_empty() => format!("{}{}", to, rest),
+                _ => query.to_string(),
+            },
+            RewriteKind::Suffix { from, to } => match strip_suffix_ignore_case(query, from) {
+                Some(rest) if !from.is_empty() => format!("{}{}", rest, to),
+                _ => query.to_string(),
+            },
+            RewriteKind::Synonyms { words } => {
+                static WORD: OnceLock<Regex> = OnceLock::new();
+                let word = WORD.get_or_init(|| Regex::new(r"\S+").expect("valid regex"));
+                word.replace_all(query, |caps: &Captures| {
+                    let found = &caps[0];
+                    words
+                        .iter()
+                        .find(|(from, _)| from.eq_ignore_ascii_case(found))
+                        .map(|(_, to)| to.clone())
+                        .unwrap_or_else(|| found.to_string())
+                })
+                .into_owned()
+            }
+        }
+    }
+}
+
+// Run the query through every step in order
+pub fn rewrite_query(steps: &[RewriteStep], query: &str) -> String {
+    steps
+        .iter()
+        .fold(query.to_string(), |query, step| step.apply(&query))
+}
+
+// Run the pipeline, recording each step's output
+pub fn trace_rewrite(steps: &[RewriteStep], query: &str) -> Vec<RewriteTrace> {
+    let mut current = query.to_string();
+    steps
+        .iter()
+        .map(|step| {
+            let output = step.apply(&current);
+            let changed = output != current;
+            current = output.clone();
+            RewriteTrace {
+                step: step.label(),
+                output,
+                changed,
+            }
+        })
+        .collect()
+}
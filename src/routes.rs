@@ -1,17 +1,46 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Json, Redirect, Response},
-    routing::get,
+    extract::{Form, Path, Query, State},
+    http::{
+        header::{
+            AUTHORIZATION, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_SECURITY_POLICY, CONTENT_TYPE, COOKIE, ETAG,
+            IF_NONE_MATCH, REFERRER_POLICY, SET_COOKIE, X_FRAME_OPTIONS,
+        },
+        HeaderMap, StatusCode,
+    },
+    response::{Html, IntoResponse, Json, Redirect, Response},
+    routing::{get, post},
     Router,
 };
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::{
-    bangs::extract_bang,
-    config::Config,
-    models::SearchQuery,
+    admin::{bang_from_form, find_bang, form_from_bang, interpolate_bang, list_bangs, preview_target, AdminError},
+    auth::{admin_session, find_user, identify, is_admin, is_admin_session, user_credential, ADMIN_SESSION_TTL},
+    bookmarks::{fuzzy_matches, parse_command, BookmarkCommand, BookmarkError},
+    config::{validate_bang, Config},
+    deprecation::{deprecated_bangs, Deprecation},
+    export::{bang_opensearch, can_export, export, ExportFormat},
+    forms::{content_security_policy, should_auto_submit, FormPost},
+    links::LinkError,
+    listing::{bang_list, selected_fields},
+    models::{
+        AdminBangForm, AdminBangInput, AdminDeleteForm, AdminLoginForm, AdminPageQuery, BangsQuery, BookmarkInput,
+        ExportQuery, LinkInput, SearchQuery,
+    },
+    opensearch::{render_description, DocumentUrls},
+    pages::{
+        admin_login_page, admin_page, blocked_page, bookmarks_page, deprecation_page, directory_page, index_page,
+        post_form_page,
+    },
+    policy::PolicyViolation,
+    resolver::{Action, BangResolver, Matched, Resolution},
+    rewrite::{trace_rewrite, RewriteTrace},
+    state::AppState,
+    storage::{now, Storage},
+    suggest::suggest,
 };
 
 /// Response structure for the /live endpoint
@@ -21,31 +50,37 @@ struct BangInfo {
     bang: Option<String>,
     bang_name: Option<String>,
     bang_icon: Option<String>,
+    bang_description: Option<String>,
+    bang_category: Option<String>,
+    bang_tags: Vec<String>,
+    bang_examples: Vec<String>,
+    bang_deprecated: bool,
+    /// Bang that searches with a deprecated one go to instead
+    bang_replaced_by: Option<String>,
+    /// Migration hint when the query used a deprecated bang
+    deprecation: Option<String>,
+    rule: Option<String>,
 }
 
-/// Add this new struct for OpenSearch response
+/// Response structure for the /debug/rewrite endpoint
 #[derive(Serialize)]
-struct OpenSearchResponse {
-    xml: String,
+struct RewriteDebug {
+    query: String,
+    steps: Vec<RewriteTrace>,
+    output: String,
 }
 
-/// Details of a bang for the /bangs endpoint
-#[derive(Serialize)]
-struct BangDetails {
-    key: String,
-    name: String,
-    icon: String,
-    url: String,
-}
+/// Cookie holding the admin interface session
+const ADMIN_COOKIE: &str = "bang_admin";
 
-/// Response structure for the /bangs endpoint
-#[derive(Serialize)]
-struct BangsList {
-    bangs: Vec<BangDetails>,
-}
+/// Cookie listing the deprecated bangs whose notice has been shown
+const NOTICE_COOKIE: &str = "bang_notices";
+
+/// Sample query used for "try it" previews on the admin page
+const DEFAULT_SAMPLE: &str = "example search";
 
 /// Creates the application router with all routes and middleware
-pub fn create_router() -> Router<Config> {
+pub fn create_router() -> Router<AppState> {
     // Create a CORS layer that allows any origin
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -53,11 +88,45 @@ pub fn create_router() -> Router<Config> {
         .allow_headers(Any);
 
     Router::new()
+        .route("/", get(index_handler))
+        .route("/directory", get(directory_handler))
         .route("/search", get(search_handler))
         .route("/health", get(health_check))
         .route("/live", get(live_handler))
+        .route("/debug/rewrite", get(rewrite_debug_handler))
         .route("/bangs", get(bangs_list_handler))
+        .route("/bangs/deprecated", get(deprecated_bangs_handler))
+        .route("/suggest", get(suggest_handler))
         .route("/opensearch.xml", get(opensearch_handler))
+        .route("/u/:user_id/opensearch.xml", get(user_opensearch_handler))
+        .route("/export/opensearch/:bang", get(export_opensearch_handler))
+        .route("/export/:format", get(export_handler))
+        .route("/api/bookmarks", get(list_bookmarks_handler).post(create_bookmark_handler))
+        .route(
+            "/api/bookmarks/:name",
+            get(get_bookmark_handler)
+                .put(update_bookmark_handler)
+                .delete(delete_bookmark_handler),
+        )
+        .route("/api/links", get(list_links_handler).post(create_link_handler))
+        .route(
+            "/api/links/:name",
+            get(get_link_handler)
+                .put(update_link_handler)
+                .delete(delete_link_handler),
+        )
+        .route("/admin", get(admin_page_handler))
+        .route("/admin/login", post(admin_login_handler))
+        .route("/admin/logout", post(admin_logout_handler))
+        .route("/admin/save", post(admin_save_handler))
+        .route("/admin/delete", post(admin_delete_handler))
+        .route("/admin/bangs", get(admin_list_bangs_handler).post(admin_create_bang_handler))
+        .route(
+            "/admin/bangs/:name",
+            get(admin_get_bang_handler)
+                .put(admin_update_bang_handler)
+                .delete(admin_delete_bang_handler),
+        )
         .layer(cors)
 }
 
@@ -66,148 +135,900 @@ async fn health_check() -> Response {
     StatusCode::OK.into_response()
 }
 
+/// Landing page with a search box
+async fn index_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+) -> Html<String> {
+    // Only carry the login into the page when it is valid
+    let login = params.login.as_deref().filter(|login| identify(&config, Some(login)).is_authenticated());
+    
+    Html(index_page(&config, login))
+}
+
+/// Searchable directory of all bangs visible to the caller
+async fn directory_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+) -> Html<String> {
+    let login = params.login.as_deref().filter(|login| identify(&config, Some(login)).is_authenticated());
+    
+    Html(directory_page(&config, login.is_some(), login))
+}
+
 /// Handler for the search endpoint that redirects based on bang commands
 async fn search_handler(
     Query(params): Query<SearchQuery>,
-    State(config): State<Config>,
-) -> impl IntoResponse {
-    let query = match params.q {
-        Some(q) => q,
-        None => return Redirect::to("https://www.google.com"),
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(query) = params.q else {
+        return Redirect::to("https://www.google.com").into_response();
     };
     
-    // Default search engine
-    let default_search = "https://www.google.com/search?q={searchTerms}";
+    let identity = identify(&config, params.login.as_deref());
+    let resolver = BangResolver::new(&config).with_storage(&storage);
+    let resolution = resolver.resolve(&query, &identity);
+    println!("Resolved '{}': {}", resolution.query, resolution.reason);
+    if let Some(error) = &resolution.error {
+        println!("{}", error);
+    }
     
-    // Extract bang if present
-    let (bang, search_term) = extract_bang(&query);
+    if let Some(deprecation) = &resolution.deprecation {
+        // Counted so it is clear when nobody uses the old key any more
+        if let Err(e) = storage.record_bang_use(&deprecation.key) {
+            println!("Failed to count use of '{}': {}", deprecation.key, e);
+        }
+        let seen = seen_notices(&headers).contains(&urlencoding::encode(&deprecation.key).as_ref());
+        if deprecation.retired || (deprecation.notice && !seen) {
+            return deprecation_response(&config, deprecation, &resolution, params.login.as_deref(), &headers);
+        }
+    }
     
-    // Check if this is an authenticated request
-    let is_authenticated = params.login.as_deref() == Some(&config.auth_token) && !config.auth_token.is_empty();
-    
-    if let Some(bang_key) = bang {
-        // First check public bangs
-        if let Some(bang_details) = config.bangs.get(bang_key) {
-            let redirect_url = bang_details.url.replace("{searchTerms}", &urlencoding::encode(search_term));
-            return Redirect::to(&redirect_url);
-        }
-        
-        // Then check private bangs if authenticated
-        if is_authenticated {
-            if let Some(bang_details) = config.private_bangs.get(bang_key) {
-                let redirect_url = bang_details.url.replace("{searchTerms}", &urlencoding::encode(search_term));
-                return Redirect::to(&redirect_url);
+    match resolution.action {
+        Action::Redirect(url) => Redirect::to(&url).into_response(),
+        Action::Post(form) => {
+            let name = match &resolution.matched {
+                Matched::Bang { key, .. } => resolver.lookup(key, &identity).map(|(details, _)| details.name.as_str()),
+                _ => None,
+            };
+            post_form_response(&config, name.unwrap_or_default(), &form, &headers)
+        }
+        Action::Blocked { target, violation } => {
+            // Logged and shown as written, so values filled in from ${...} stay hidden
+            let written = match &resolution.matched {
+                Matched::Bang { key, .. } => resolver
+                    .lookup(key, &identity)
+                    .and_then(|(details, _)| resolver.bang_target(&details.as_written(), &resolution.term).ok()),
+                _ => None,
+            };
+            match written {
+                Some(written) if written != target => {
+                    blocked_response(&config, &written, &violation.as_written(&written))
+                }
+                _ => blocked_response(&config, &target, &violation),
+            }
+        }
+        Action::Invalid(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+        Action::Bookmark => {
+            let (Matched::Bookmark { bang }, Some(owner)) = (&resolution.matched, identity.user_name()) else {
+                return StatusCode::UNAUTHORIZED.into_response();
+            };
+            match parse_command(bang, &resolution.term) {
+                Some(command) => bookmark_command(&config, &storage, owner, command),
+                None => StatusCode::BAD_REQUEST.into_response(),
             }
         }
-        
-        println!("Bang '{}' not found in configuration", bang_key);
     }
+}
+
+/// Deprecated bangs whose notice this browser has already seen, as stored
+/// in the cookie
+fn seen_notices(headers: &HeaderMap) -> Vec<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .filter(|(name, _)| *name == NOTICE_COOKIE)
+        .flat_map(|(_, seen)| seen.split('|'))
+        .collect()
+}
+
+/// Notice page for a deprecated bang. Retired bangs get it every time; for
+/// others it is shown once and a cookie remembers that.
+fn deprecation_response(
+    config: &Config,
+    deprecation: &Deprecation,
+    resolution: &Resolution,
+    login: Option<&str>,
+    headers: &HeaderMap,
+) -> Response {
+    let search = |query: &str| match login {
+        Some(login) => format!("/search?q={}&login={}", urlencoding::encode(query), urlencoding::encode(login)),
+        None => format!("/search?q={}", urlencoding::encode(query)),
+    };
+    // Carry on with the replacement so people learn the new key
+    let next = match (&deprecation.replaced_by, &resolution.action) {
+        (Some(replacement), _) => Some(search(format!("{} {}", replacement, resolution.term).trim_end())),
+        (None, _) if deprecation.retired => None,
+        (None, Action::Redirect(url)) => Some(url.clone()),
+        (None, _) => Some(search(&resolution.query)),
+    };
+    let page = Html(deprecation_page(config, deprecation, next.as_deref()));
+    
+    if deprecation.retired {
+        return (StatusCode::GONE, [(CACHE_CONTROL, "no-store")], page).into_response();
+    }
+    
+    // The cookie is replaced as a whole, so it keeps the notices seen before
+    let key = urlencoding::encode(&deprecation.key);
+    let mut seen = seen_notices(headers);
+    seen.push(&key);
+    let cookie = format!("{}={}; Path=/; Max-Age=31536000; HttpOnly; SameSite=Lax", NOTICE_COOKIE, seen.join("|"));
+    ([(SET_COOKIE, cookie), (CACHE_CONTROL, "no-store".to_string())], page).into_response()
+}
+
+/// Warning page shown instead of redirecting somewhere the policy forbids
+fn blocked_response(config: &Config, target: &str, violation: &PolicyViolation) -> Response {
+    println!("Blocked redirect to {}: {}", target, violation);
+    (StatusCode::FORBIDDEN, Html(blocked_page(config, target, violation))).into_response()
+}
+
+/// Redirect to a URL if the global redirect policy allows it, otherwise show
+/// a warning page instead
+fn checked_redirect(config: &Config, target: &str) -> Response {
+    match config.redirect_policy.check(target) {
+        Ok(_) => Redirect::to(target).into_response(),
+        Err(violation) => blocked_response(config, target, &violation),
+    }
+}
+
+/// Page that submits a search to a POST-only site
+fn post_form_response(config: &Config, name: &str, form: &FormPost, headers: &HeaderMap) -> Response {
+    let sec_fetch_site = headers.get("sec-fetch-site").and_then(|v| v.to_str().ok());
+    let auto_submit = should_auto_submit(sec_fetch_site);
+    
+    (
+        [
+            (CONTENT_SECURITY_POLICY, content_security_policy(&form.action)),
+            (CACHE_CONTROL, "no-store".to_string()),
+            (REFERRER_POLICY, "no-referrer".to_string()),
+            (X_FRAME_OPTIONS, "DENY".to_string()),
+        ],
+        Html(post_form_page(config, name, form, auto_submit)),
+    ).into_response()
+}
+
+/// Run a bookmark command typed into the search box
+fn bookmark_command(config: &Config, storage: &Storage, owner: &str, command: BookmarkCommand) -> Response {
+    let result = match command {
+        BookmarkCommand::Open(name) => storage.list_bookmarks(owner).map(|bookmarks| {
+            let matches = fuzzy_matches(bookmarks, name);
+            // Jump straight to an exact match, otherwise show the candidates
+            match matches.first() {
+                Some(bookmark) if bookmark.name.eq_ignore_ascii_case(name) => {
+                    checked_redirect(config, &bookmark.url)
+                }
+                _ if matches.is_empty() => {
+                    Html(bookmarks_page(config, &format!("No bookmarks match '{}'", name), &[])).into_response()
+                }
+                _ => Html(bookmarks_page(config, &format!("Bookmarks matching '{}'", name), &matches)).into_response(),
+            }
+        }),
+        BookmarkCommand::Add { name, url } => storage.add_bookmark(owner, name, url).map(|bookmark| {
+            let message = format!("Saved bookmark '{}'", bookmark.name);
+            Html(bookmarks_page(config, &message, &[bookmark])).into_response()
+        }),
+        BookmarkCommand::Delete(name) => storage.delete_bookmark(owner, name).map(|deleted| {
+            let message = if deleted {
+                format!("Deleted bookmark '{}'", name)
+            } else {
+                format!("Bookmark '{}' not found", name)
+            };
+            Html(bookmarks_page(config, &message, &[])).into_response()
+        }),
+    };
+    
+    result.unwrap_or_else(|e| bookmark_error(e).into_response())
+}
+
+/// Map a bookmark error to an HTTP status and message
+fn bookmark_error(e: BookmarkError) -> (StatusCode, String) {
+    let status = match e {
+        BookmarkError::InvalidName(_) | BookmarkError::InvalidUrl(_) => StatusCode::BAD_REQUEST,
+        BookmarkError::NotFound(_) => StatusCode::NOT_FOUND,
+        BookmarkError::AlreadyExists(_) => StatusCode::CONFLICT,
+        BookmarkError::Storage(_) => {
+            println!("{}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
+
+/// Map a link error to an HTTP status and message
+fn link_error(e: LinkError) -> (StatusCode, String) {
+    let status = match e {
+        LinkError::InvalidName(_) | LinkError::InvalidUrl(_) => StatusCode::BAD_REQUEST,
+        LinkError::NotFound(_) => StatusCode::NOT_FOUND,
+        LinkError::AlreadyExists(_) => StatusCode::CONFLICT,
+        LinkError::Forbidden(_) => StatusCode::FORBIDDEN,
+        LinkError::Storage(_) => {
+            println!("{}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
+
+/// List the caller's bookmarks
+async fn list_bookmarks_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    match storage.list_bookmarks(owner) {
+        Ok(bookmarks) => Json(bookmarks).into_response(),
+        Err(e) => bookmark_error(e).into_response(),
+    }
+}
+
+/// Create or replace a bookmark
+async fn create_bookmark_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+    Json(input): Json<BookmarkInput>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    let (Some(name), Some(url)) = (input.name.as_deref(), input.url.as_deref()) else {
+        return (StatusCode::BAD_REQUEST, "Both name and url are required").into_response();
+    };
+    
+    match storage.add_bookmark(owner, name, url) {
+        Ok(bookmark) => (StatusCode::CREATED, Json(bookmark)).into_response(),
+        Err(e) => bookmark_error(e).into_response(),
+    }
+}
+
+/// Get a single bookmark by name
+async fn get_bookmark_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    match storage.get_bookmark(owner, &name) {
+        Ok(Some(bookmark)) => Json(bookmark).into_response(),
+        Ok(None) => bookmark_error(BookmarkError::NotFound(name)).into_response(),
+        Err(e) => bookmark_error(e).into_response(),
+    }
+}
+
+/// Rename a bookmark or change its URL
+async fn update_bookmark_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+    Json(input): Json<BookmarkInput>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    match storage.update_bookmark(owner, &name, input.name.as_deref(), input.url.as_deref()) {
+        Ok(bookmark) => Json(bookmark).into_response(),
+        Err(e) => bookmark_error(e).into_response(),
+    }
+}
+
+/// Delete a bookmark
+async fn delete_bookmark_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    match storage.delete_bookmark(owner, &name) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => bookmark_error(BookmarkError::NotFound(name)).into_response(),
+        Err(e) => bookmark_error(e).into_response(),
+    }
+}
+
+/// Map an admin API error to an HTTP status and message
+fn admin_error(e: AdminError) -> (StatusCode, String) {
+    let status = match e {
+        AdminError::Invalid(_) => StatusCode::BAD_REQUEST,
+        AdminError::NotFound(_) => StatusCode::NOT_FOUND,
+        AdminError::AlreadyExists(_) => StatusCode::CONFLICT,
+        AdminError::Io(_) | AdminError::Yaml(_) => {
+            println!("{}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
+
+/// Check the `Authorization: Bearer` header against the admin token
+fn admin_authorized(config: &Config, headers: &HeaderMap) -> bool {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    is_admin(config, token)
+}
+
+/// List all public and private bangs
+async fn admin_list_bangs_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let config = state.config();
+    if !admin_authorized(&config, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    Json(list_bangs(&config)).into_response()
+}
+
+/// Add a bang, live and in the overlay file
+async fn admin_create_bang_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(input): Json<AdminBangInput>,
+) -> Response {
+    if !admin_authorized(&state.config(), &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    let (Some(name), Some(bang)) = (input.name, input.bang) else {
+        return (StatusCode::BAD_REQUEST, "Both name and bang are required").into_response();
+    };
+    let private = input.private.unwrap_or(false);
     
-    // No bang found or bang not recognized, redirect to default search engine
-    let redirect_url = default_search.replace("{searchTerms}", &urlencoding::encode(&query));
-    println!("Redirecting to default: {}", redirect_url);
-    Redirect::to(&redirect_url)
+    match state.update_bangs(|config, overlay| overlay.create(config, &name, private, bang)) {
+        Ok(bang) => (StatusCode::CREATED, Json(bang)).into_response(),
+        Err(e) => admin_error(e).into_response(),
+    }
+}
+
+/// Get a single bang by name
+async fn admin_get_bang_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let config = state.config();
+    if !admin_authorized(&config, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match find_bang(&config, &name) {
+        Some(bang) => Json(bang).into_response(),
+        None => admin_error(AdminError::NotFound(name)).into_response(),
+    }
+}
+
+/// Rename a bang, move it between public and private, or replace its details
+async fn admin_update_bang_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(input): Json<AdminBangInput>,
+) -> Response {
+    if !admin_authorized(&state.config(), &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    let result = state.update_bangs(|config, overlay| {
+        overlay.update(config, &name, input.name.as_deref(), input.private, input.bang)
+    });
+    match result {
+        Ok(bang) => Json(bang).into_response(),
+        Err(e) => admin_error(e).into_response(),
+    }
+}
+
+/// Delete a bang
+async fn admin_delete_bang_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    if !admin_authorized(&state.config(), &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match state.update_bangs(|config, overlay| overlay.delete(config, &name)) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => admin_error(e).into_response(),
+    }
+}
+
+/// Check the admin session cookie set by /admin/login
+fn admin_logged_in(config: &Config, headers: &HeaderMap) -> bool {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .any(|(name, value)| name == ADMIN_COOKIE && is_admin_session(config, value))
+}
+
+/// Admin pages are never cached or framed
+fn admin_html(status: StatusCode, page: String) -> Response {
+    (
+        status,
+        [(CACHE_CONTROL, "no-store"), (X_FRAME_OPTIONS, "DENY")],
+        Html(page),
+    ).into_response()
+}
+
+/// Back to the admin page, keeping the sample query
+fn admin_redirect(edit: Option<&str>, sample: &str) -> Response {
+    let mut target = format!("/admin?sample={}", urlencoding::encode(sample));
+    if let Some(edit) = edit {
+        target.push_str(&format!("&edit={}", urlencoding::encode(edit)));
+    }
+    Redirect::to(&target).into_response()
+}
+
+/// Bang management page, or the login form without a session
+async fn admin_page_handler(
+    Query(params): Query<AdminPageQuery>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Response {
+    if !admin_logged_in(&config, &headers) {
+        return admin_html(StatusCode::OK, admin_login_page(&config, ""));
+    }
+    
+    let sample = params.sample.unwrap_or_else(|| DEFAULT_SAMPLE.to_string());
+    let editing = params.edit.as_deref().and_then(|name| find_bang(&config, name));
+    let page = match &editing {
+        Some(bang) => {
+            let preview = preview_target(&config, &bang.bang, &sample);
+            admin_page(&config, "", &form_from_bang(bang, &sample), Some((&bang.bang, preview)))
+        }
+        None => {
+            let message = match &params.edit {
+                Some(name) => format!("Bang '{}' not found", name),
+                None => String::new(),
+            };
+            let form = AdminBangForm { sample, ..Default::default() };
+            admin_page(&config, &message, &form, None)
+        }
+    };
+    admin_html(StatusCode::OK, page)
+}
+
+/// Start an admin session. The cookie is SameSite=Strict, so forms posted
+/// from other sites arrive without it.
+async fn admin_login_handler(State(config): State<Arc<Config>>, Form(form): Form<AdminLoginForm>) -> Response {
+    let Some(session) = admin_session(&config, now()) else {
+        let message = "The admin interface is disabled until admin.token is set";
+        return admin_html(StatusCode::UNAUTHORIZED, admin_login_page(&config, message));
+    };
+    if !is_admin(&config, Some(form.token.as_str())) {
+        return admin_html(StatusCode::UNAUTHORIZED, admin_login_page(&config, "Wrong admin token"));
+    }
+    
+    let secure = if config.host_url.as_deref().is_some_and(|url| url.starts_with("https://")) { "; Secure" } else { "" };
+    let cookie = format!(
+        "{}={}; Path=/admin; HttpOnly; SameSite=Strict; Max-Age={}{}",
+        ADMIN_COOKIE, session, ADMIN_SESSION_TTL, secure
+    );
+    ([(SET_COOKIE, cookie)], Redirect::to("/admin")).into_response()
+}
+
+/// End the admin session
+async fn admin_logout_handler() -> Response {
+    let cookie = format!("{}=; Path=/admin; HttpOnly; SameSite=Strict; Max-Age=0", ADMIN_COOKIE);
+    ([(SET_COOKIE, cookie)], Redirect::to("/admin")).into_response()
+}
+
+/// Save the add/edit form, or preview it without saving
+async fn admin_save_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<AdminBangForm>,
+) -> Response {
+    let config = state.config();
+    if !admin_logged_in(&config, &headers) {
+        return admin_html(StatusCode::UNAUTHORIZED, admin_login_page(&config, "Please log in again"));
+    }
+    
+    let key = form.key.trim().to_string();
+    let private = form.private.is_some();
+    let existing = find_bang(&config, &form.original).map(|bang| bang.bang).unwrap_or_default();
+    let bang = bang_from_form(&form, existing);
+    
+    if form.action == "test" {
+        let checked = interpolate_bang(&config, &key, &bang).and_then(|bang| validate_bang(&key, &bang));
+        let message = checked.err().map(|e| e.to_string()).unwrap_or_default();
+        let preview = preview_target(&config, &bang, &form.sample);
+        return admin_html(StatusCode::OK, admin_page(&config, &message, &form, Some((&bang, preview))));
+    }
+    
+    let result = state.update_bangs(|config, overlay| {
+        if form.original.is_empty() {
+            overlay.create(config, &key, private, bang)
+        } else {
+            overlay.update(config, &form.original, Some(&key), Some(private), Some(bang))
+        }
+    });
+    match result {
+        Ok(saved) => admin_redirect(Some(&saved.name), &form.sample),
+        Err(e) => {
+            let (status, message) = admin_error(e);
+            admin_html(status, admin_page(&state.config(), &message, &form, None))
+        }
+    }
+}
+
+/// Delete a bang from the admin page
+async fn admin_delete_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<AdminDeleteForm>,
+) -> Response {
+    let config = state.config();
+    if !admin_logged_in(&config, &headers) {
+        return admin_html(StatusCode::UNAUTHORIZED, admin_login_page(&config, "Please log in again"));
+    }
+    
+    match state.update_bangs(|config, overlay| overlay.delete(config, &form.name)) {
+        Ok(()) => admin_redirect(None, &form.sample),
+        Err(e) => {
+            let (status, message) = admin_error(e);
+            let page_form = AdminBangForm { sample: form.sample, ..Default::default() };
+            admin_html(status, admin_page(&config, &message, &page_form, None))
+        }
+    }
+}
+
+/// List all go-links
+async fn list_links_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    if !identify(&config, params.login.as_deref()).is_authenticated() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match storage.list_links() {
+        Ok(links) => Json(links).into_response(),
+        Err(e) => link_error(e).into_response(),
+    }
+}
+
+/// Create a go-link owned by the caller
+async fn create_link_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+    Json(input): Json<LinkInput>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    let Some(owner) = identity.user_name() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    
+    let (Some(name), Some(url)) = (input.name.as_deref(), input.url.as_deref()) else {
+        return (StatusCode::BAD_REQUEST, "Both name and url are required").into_response();
+    };
+    
+    match storage.create_link(owner, name, url) {
+        Ok(link) => (StatusCode::CREATED, Json(link)).into_response(),
+        Err(e) => link_error(e).into_response(),
+    }
+}
+
+/// Get a single go-link, including its owner and visit count
+async fn get_link_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    if !identify(&config, params.login.as_deref()).is_authenticated() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match storage.get_link(&name) {
+        Ok(Some(link)) => Json(link).into_response(),
+        Ok(None) => link_error(LinkError::NotFound(name)).into_response(),
+        Err(e) => link_error(e).into_response(),
+    }
+}
+
+/// Rename a go-link or change its URL
+async fn update_link_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+    Json(input): Json<LinkInput>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    if !identity.is_authenticated() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match storage.update_link(&identity, &name, input.name.as_deref(), input.url.as_deref()) {
+        Ok(link) => Json(link).into_response(),
+        Err(e) => link_error(e).into_response(),
+    }
+}
+
+/// Delete a go-link
+async fn delete_link_handler(
+    Path(name): Path<String>,
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    let identity = identify(&config, params.login.as_deref());
+    if !identity.is_authenticated() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    
+    match storage.delete_link(&identity, &name) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => link_error(e).into_response(),
+    }
 }
 
 /// Handler for the live endpoint that returns information about the current query
 async fn live_handler(
     Query(params): Query<SearchQuery>,
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
 ) -> impl IntoResponse {
-    let query = params.q.unwrap_or_default();
+    let identity = identify(&config, params.login.as_deref());
+    let resolver = BangResolver::new(&config);
+    let resolution = resolver.resolve(&params.q.unwrap_or_default(), &identity);
     
-    // Extract bang if present
-    let (bang, search_term) = extract_bang(&query);
+    // Only include valid bangs in the response. A deprecated bang is
+    // described as typed, even when searches go through its replacement.
+    let key = match (&resolution.deprecation, &resolution.matched) {
+        (Some(deprecation), _) => Some(&deprecation.key),
+        (None, Matched::Bang { key, .. }) => Some(key),
+        _ => None,
+    };
+    let bang = key.and_then(|key| resolver.lookup(key, &identity).map(|(details, _)| (key.clone(), details)));
+    let deprecation = resolution.deprecation.as_ref().map(Deprecation::message);
     
-    // Check if this is an authenticated request
-    let is_authenticated = params.login.as_deref() == Some(&config.auth_token) && !config.auth_token.is_empty();
-    
-    // Only include valid bangs in the response
-    let (valid_bang, bang_name, bang_icon) = match bang {
-        Some(b) => {
-            if let Some(details) = config.bangs.get(b) {
-                (Some(b.to_string()), Some(details.name.clone()), Some(details.icon.clone()))
-            } else if is_authenticated {
-                if let Some(details) = config.private_bangs.get(b) {
-                    (Some(b.to_string()), Some(details.name.clone()), Some(details.icon.clone()))
-                } else {
-                    (None, None, None)
-                }
-            } else {
-                (None, None, None)
-            }
-        },
-        None => (None, None, None)
+    // Report the rule that would handle the query when no bang does
+    let rule = match &resolution.matched {
+        Matched::Rule { name } => Some(name.clone()),
+        _ => None,
     };
     
     Json(BangInfo {
-        query: search_term.to_string(),
-        bang: valid_bang,
-        bang_name,
-        bang_icon,
+        query: resolution.term,
+        bang_name: bang.as_ref().map(|(_, details)| details.name.clone()),
+        bang_icon: bang.as_ref().map(|(_, details)| details.icon.clone()),
+        bang_description: bang.as_ref().and_then(|(_, details)| details.description.clone()),
+        bang_category: bang.as_ref().and_then(|(_, details)| details.category.clone()),
+        bang_tags: bang.as_ref().map(|(_, details)| details.tags.clone()).unwrap_or_default(),
+        bang_examples: bang.as_ref().map(|(_, details)| details.examples.clone()).unwrap_or_default(),
+        bang_deprecated: bang.as_ref().is_some_and(|(_, details)| details.is_deprecated()),
+        bang_replaced_by: bang.as_ref().and_then(|(_, details)| details.replaced_by.clone()),
+        deprecation,
+        bang: bang.map(|(key, _)| key),
+        rule,
     })
 }
 
-/// Handler for the bangs list endpoint that returns all available bangs
-async fn bangs_list_handler(
+/// Debug endpoint showing the query after each rewrite step
+async fn rewrite_debug_handler(
     Query(params): Query<SearchQuery>,
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
 ) -> impl IntoResponse {
+    let query = params.q.unwrap_or_default();
+    let steps = trace_rewrite(&config.rewrites, &query);
+    let output = steps.last().map(|step| step.output.clone()).unwrap_or_else(|| query.clone());
+    
+    Json(RewriteDebug { query, steps, output })
+}
+
+/// ETag for a /bangs response, from the bangs' hash and the request variant
+fn bangs_etag(fingerprint: &str, params: &BangsQuery, is_authenticated: bool) -> String {
+    let variant = format!(
+        "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}",
+        params.q, params.category, params.visibility, params.limit, params.offset, params.fields, is_authenticated
+    );
+    let digest = Sha256::digest(variant.as_bytes());
+    format!("\"{}-{}\"", fingerprint, hex::encode(&digest[..8]))
+}
+
+/// Handler for the bangs list endpoint with search, filtering and pagination
+async fn bangs_list_handler(
+    Query(params): Query<BangsQuery>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let config = state.config();
     // Check if this is an authenticated request
-    let is_authenticated = params.login.as_deref() == Some(&config.auth_token) && !config.auth_token.is_empty();
+    let is_authenticated = identify(&config, params.login.as_deref()).is_authenticated();
     
-    let mut bangs_list = Vec::new();
+    // Validate the requested fields before doing any work
+    if let Err(e) = selected_fields(&params) {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
     
-    // Add public bangs
-    for (key, details) in &config.bangs {
-        bangs_list.push(BangDetails {
-            key: key.clone(),
-            name: details.name.clone(),
-            icon: details.icon.clone(),
-            url: details.url.clone(),
-        });
+    // The response only depends on the config, the parameters and the caller's access
+    let etag = bangs_etag(&state.bangs_fingerprint(), &params, is_authenticated);
+    let etag_matches = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+        .unwrap_or(false);
+    if etag_matches {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response();
     }
     
-    // Add private bangs if authenticated
-    if is_authenticated {
-        for (key, details) in &config.private_bangs {
-            bangs_list.push(BangDetails {
-                key: key.clone(),
-                name: details.name.clone(),
-                icon: details.icon.clone(),
-                url: details.url.clone(),
-            });
+    match bang_list(&config, &params, is_authenticated) {
+        Ok(list) => ([(ETAG, etag)], Json(list)).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+/// Deprecated bangs with how often each is still used, to tell when one can
+/// be deleted. Counts change with every search, so this is never cached.
+async fn deprecated_bangs_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<Storage>,
+) -> Response {
+    let is_authenticated = identify(&config, params.login.as_deref()).is_authenticated();
+    
+    match deprecated_bangs(&config, &storage, is_authenticated) {
+        Ok(bangs) => ([(CACHE_CONTROL, "no-store")], Json(bangs)).into_response(),
+        Err(e) => {
+            println!("Failed to read bang use counts: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
         }
     }
+}
+
+/// Handler for OpenSearch suggestions, mixing bang and upstream completions
+async fn suggest_handler(
+    Query(params): Query<SearchQuery>,
+    State(config): State<Arc<Config>>,
+) -> impl IntoResponse {
+    let query = params.q.unwrap_or_default();
     
-    // Sort the list by key for consistent output
-    bangs_list.sort_by(|a, b| a.key.cmp(&b.key));
+    // Check if this is an authenticated request
+    let is_authenticated = identify(&config, params.login.as_deref()).is_authenticated();
+    
+    let suggestions = suggest(&query, &config, is_authenticated).await;
     
-    Json(BangsList { bangs: bangs_list })
+    (
+        [("Content-Type", "application/x-suggestions+json")],
+        Json(suggestions)
+    )
+}
+
+/// Render an OpenSearch description document as an XML response
+fn opensearch_response(config: &Config, urls: &DocumentUrls) -> Response {
+    match render_description(&config.opensearch, urls) {
+        Ok(xml) => (
+            [("Content-Type", "application/opensearchdescription+xml")],
+            xml
+        ).into_response(),
+        Err(e) => {
+            println!("Failed to render OpenSearch description: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
 }
 
 /// Handler for the OpenSearch description document
-async fn opensearch_handler(State(config): State<Config>) -> impl IntoResponse {
+async fn opensearch_handler(State(config): State<Arc<Config>>) -> Response {
     let host_url = config.host_url.clone().unwrap_or_else(|| "http://localhost:3000".to_string());
     
-    // Create the OpenSearch description XML
-    let xml = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
-  <ShortName>Bang Search</ShortName>
-  <Description>Search with bangs</Description>
-  <InputEncoding>UTF-8</InputEncoding>
-  <Image width="16" height="16" type="image/x-icon">https://www.cereal.sh/favicon.ico</Image>
-  <Url type="text/html" method="get" template="{}/search?q={{searchTerms}}"/>
-  <Url type="application/x-suggestions+json" 
-       template="https://www.google.com/complete/search?client=chrome&amp;q={{searchTerms}}"/>
-</OpenSearchDescription>"#,
-        host_url
-    );
+    opensearch_response(&config, &DocumentUrls::for_host(&host_url))
+}
+
+/// Handler for bang exports: keyword bookmarks, a Chrome policy and launcher
+/// quicklinks that search each site directly. Private bangs are included
+/// for authenticated requests.
+async fn export_handler(
+    Path(format): Path<String>,
+    Query(params): Query<ExportQuery>,
+    State(config): State<Arc<Config>>,
+) -> Response {
+    let format: ExportFormat = match format.parse() {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+    };
+    let is_authenticated = identify(&config, params.login.as_deref()).is_authenticated();
     
-    // Set the content type to XML
     (
-        [("Content-Type", "application/opensearchdescription+xml")],
-        xml
-    )
-} 
\ No newline at end of file
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", format.file_name())),
+            (CACHE_CONTROL, "private".to_string()),
+        ],
+        export(&config, format, is_authenticated),
+    ).into_response()
+}
+
+/// Handler for an OpenSearch document that searches a single bang's site
+/// directly; "/export/opensearch/gh" and "/export/opensearch/!gh" both work
+async fn export_opensearch_handler(
+    Path(bang): Path<String>,
+    Query(params): Query<ExportQuery>,
+    State(config): State<Arc<Config>>,
+) -> Response {
+    let key = if bang.starts_with('!') { bang } else { format!("!{}", bang) };
+    let identity = identify(&config, params.login.as_deref());
+    
+    let details = match BangResolver::new(&config).lookup(&key, &identity) {
+        Some((details, _)) if can_export(details) => details,
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    
+    let host_url = config.host_url.clone().unwrap_or_else(|| "http://localhost:3000".to_string());
+    let mut self_url = format!("{}/export/opensearch/{}", host_url.trim_end_matches('/'), urlencoding::encode(&key));
+    if let Some(login) = &params.login {
+        self_url = format!("{}?login={}", self_url, urlencoding::encode(login));
+    }
+    
+    match bang_opensearch(&key, details, &self_url) {
+        Ok(xml) => (
+            [(CONTENT_TYPE, "application/opensearchdescription+xml"), (CACHE_CONTROL, "private")],
+            xml
+        ).into_response(),
+        Err(e) => {
+            println!("Failed to render OpenSearch description: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Handler for a user's OpenSearch document, whose templates carry their credential
+async fn user_opensearch_handler(
+    Path(user_id): Path<String>,
+    State(config): State<Arc<Config>>,
+) -> Response {
+    // Unknown and revoked users get the same response
+    if find_user(&config, &user_id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    
+    let host_url = config.host_url.clone().unwrap_or_else(|| "http://localhost:3000".to_string());
+    let login = urlencoding::encode(&user_credential(&config, &user_id)).into_owned();
+    
+    let mut urls = DocumentUrls::for_host(&host_url);
+    urls.search = format!("{}&login={}", urls.search, login);
+    urls.suggest = urls.suggest.map(|suggest| format!("{}&login={}", suggest, login));
+    urls.self_url = format!("{}/u/{}/opensearch.xml", host_url.trim_end_matches('/'), urlencoding::encode(&user_id));
+    
+    opensearch_response(&config, &urls)
+}
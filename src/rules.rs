@@ -0,0 +1,83 @@
+use regex::Regex;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// A regex compiled when the config is loaded, so bad patterns fail early
+#[derive(Debug, Clone)]
+pub struct RulePattern(pub Regex);
+
+impl Serialize for RulePattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RulePattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(RulePattern)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// Patterns are written as strings in the config
+impl JsonSchema for RulePattern {
+    fn schema_name() -> String {
+        "RulePattern".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = String::json_schema(gen).into_object();
+        schema.format = Some("regex".to_string());
+        schema.into()
+    }
+}
+
+// Route queries matching a pattern without needing a bang, e.g. PROJ-1234 to Jira
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct Rule {
+    pub name: String,
+    pub pattern: RulePattern,
+    // Target with {0} for the whole match, {1}.. for groups and {name} for named groups
+    pub url: String,
+    // Higher priorities are tried first; equal priorities keep config order
+    #[serde(default)]
+    pub priority: i32,
+    // Only applies to authenticated requests, like private_bangs
+    #[serde(default)]
+    pub private: bool,
+}
+
+impl Rule {
+    // Build the target URL if the pattern matches; anchor patterns with ^ and $
+    // to require the whole query to match
+    pub fn apply(&self, query: &str) -> Option<String> {
+        let captures = self.pattern.0.captures(query.trim())?;
+
+        let mut url = self.url.clone();
+        for name in self.pattern.0.capture_names().flatten() {
+            let value = captures.name(name).map(|m| m.as_str()).unwrap_or("");
+            url = url.replace(&format!("{{{}}}", name), &urlencoding::encode(value));
+        }
+        for i in 0..captures.len() {
+            let value = captures.get(i).map(|m| m.as_str()).unwrap_or("");
+            url = url.replace(&format!("{{{}}}", i), &urlencoding::encode(value));
+        }
+        Some(url)
+    }
+}
+
+// Find the highest-priority rule matching a query, with its target URL
+pub fn match_rule<'a>(rules: &'a [Rule], query: &str, is_authenticated: bool) -> Option<(&'a Rule, String)> {
+    let mut candidates: Vec<&Rule> = rules
+        .iter()
+        .filter(|rule| is_authenticated || !rule.private)
+        .collect();
+    // Stable sort keeps config order among equal priorities
+    candidates.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+    candidates
+        .into_iter()
+        .find_map(|rule| rule.apply(query).map(|url| (rule, url)))
+}
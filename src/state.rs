@@ -0,0 +1,97 @@
+use axum::extract::FromRef;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::{
+    admin::{AdminError, BangOverlay},
+    config::Config,
+    listing::bangs_fingerprint,
+    storage::Storage,
+};
+
+// The config in effect, with the hash of its bangs worked out once when it
+// is swapped in rather than on every request
+struct Live {
+    config: Arc<Config>,
+    fingerprint: String,
+}
+
+impl Live {
+    fn new(config: Config) -> Self {
+        let fingerprint = bangs_fingerprint(&config);
+        Live { config: Arc::new(config), fingerprint }
+    }
+}
+
+// The config as loaded from disk together with the admin overlay on top of it
+struct BangSources {
+    base: Config,
+    overlay: BangOverlay,
+}
+
+// Shared state handed to every request handler
+#[derive(Clone)]
+pub struct AppState {
+    live: Arc<RwLock<Live>>,
+    sources: Arc<Mutex<BangSources>>,
+    pub storage: Storage,
+}
+
+impl AppState {
+    // Build the state for a config, opening its configured storage and
+    // applying the admin overlay
+    pub fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let storage = Storage::open(&config.storage)?;
+        let overlay = BangOverlay::for_config(&config)?;
+        let live = overlay.applied_to(&config)?;
+
+        Ok(AppState {
+            live: Arc::new(RwLock::new(Live::new(live))),
+            sources: Arc::new(Mutex::new(BangSources { base: config, overlay })),
+            storage,
+        })
+    }
+
+    // The config currently in effect
+    pub fn config(&self) -> Arc<Config> {
+        self.live.read().unwrap_or_else(|e| e.into_inner()).config.clone()
+    }
+
+    // Hash of the bangs currently in effect, for ETags
+    pub fn bangs_fingerprint(&self) -> String {
+        self.live.read().unwrap_or_else(|e| e.into_inner()).fingerprint.clone()
+    }
+
+    // Change the admin overlay and swap in the resulting config. The change
+    // is validated like a config file and saved before it goes live, so a
+    // failed write leaves both the file and the running config untouched.
+    pub fn update_bangs<T>(
+        &self,
+        change: impl FnOnce(&Config, &mut BangOverlay) -> Result<T, AdminError>,
+    ) -> Result<T, AdminError> {
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut overlay = sources.overlay.clone();
+        let result = change(&self.config(), &mut overlay)?;
+
+        let live = overlay.applied_to(&sources.base)?;
+        if let Some(path) = &sources.base.admin.overlay {
+            overlay.save(path)?;
+        }
+
+        sources.overlay = overlay;
+        *self.live.write().unwrap_or_else(|e| e.into_inner()) = Live::new(live);
+        Ok(result)
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config()
+    }
+}
+
+impl FromRef<AppState> for Storage {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
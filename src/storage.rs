@@ -0,0 +1,85 @@
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::StorageConfig;
+
+// Schema migrations, applied in order and tracked with PRAGMA user_version
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE bookmarks (
+        owner TEXT NOT NULL,
+        name TEXT NOT NULL COLLATE NOCASE,
+        url TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (owner, name)
+    )",
+    "CREATE TABLE links (
+        name TEXT PRIMARY KEY COLLATE NOCASE,
+        url TEXT NOT NULL,
+        owner TEXT NOT NULL,
+        visits INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    )",
+    "CREATE TABLE bang_uses (
+        key TEXT PRIMARY KEY,
+        uses INTEGER NOT NULL DEFAULT 0,
+        last_used INTEGER NOT NULL
+    )",
+];
+
+// Shared handle to the SQLite database holding user data
+#[derive(Clone)]
+pub struct Storage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Storage {
+    // Open the configured database, or an in-memory one when no path is set
+    pub fn open(config: &StorageConfig) -> rusqlite::Result<Self> {
+        let conn = match &config.path {
+            Some(path) => {
+                // Create the data directory on first start
+                if let Some(parent) = Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent).map_err(|e| {
+                        rusqlite::Error::InvalidPath(format!("{}: {}", parent.display(), e).into())
+                    })?;
+                }
+                Connection::open(path)?
+            }
+            None => Connection::open_in_memory()?,
+        };
+        migrate(&conn)?;
+
+        Ok(Storage {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::open(&StorageConfig::default())
+    }
+
+    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock cannot leave SQLite in a bad state
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(migration)?;
+        conn.execute_batch(&format!("PRAGMA user_version = {}", i + 1))?;
+    }
+    Ok(())
+}
+
+// Current time as seconds since the Unix epoch
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
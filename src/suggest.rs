@@ -0,0 +1,151 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use std::sync::OnceLock;
+#[cfg(feature = "server")]
+use std::time::Duration;
+
+#[cfg(feature = "server")]
+use crate::config::{BangDetails, Config};
+
+// Maximum number of completions returned in a single response
+#[cfg(feature = "server")]
+const MAX_SUGGESTIONS: usize = 10;
+
+// Upstream provider used to complete plain search terms
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum SuggestProvider {
+    #[default]
+    None,
+    Google,
+    DuckDuckGo,
+    // Fixed list of completions, used for tests and offline setups
+    Mock {
+        #[serde(default)]
+        suggestions: Vec<String>,
+    },
+}
+
+// OpenSearch suggestion response: [query, completions, descriptions, urls]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Suggestions(pub String, pub Vec<String>, pub Vec<String>, pub Vec<String>);
+
+// Fetching completions needs an HTTP client, which comes with the server
+#[cfg(feature = "server")]
+impl SuggestProvider {
+    // Fetch completions for a term from the upstream provider
+    pub async fn fetch(&self, term: &str) -> Vec<String> {
+        let url = match self {
+            SuggestProvider::None => return Vec::new(),
+            SuggestProvider::Mock { suggestions } => {
+                let term = term.to_lowercase();
+                return suggestions
+                    .iter()
+                    .filter(|s| s.to_lowercase().starts_with(&term))
+                    .cloned()
+                    .collect();
+            }
+            SuggestProvider::Google => format!(
+                "https://suggestqueries.google.com/complete/search?client=firefox&q={}",
+                urlencoding::encode(term)
+            ),
+            SuggestProvider::DuckDuckGo => format!(
+                "https://duckduckgo.com/ac/?type=list&q={}",
+                urlencoding::encode(term)
+            ),
+        };
+
+        match fetch_upstream(&url).await {
+            Ok(suggestions) => suggestions,
+            Err(e) => {
+                println!("Suggestion provider request failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+// Both Google and DuckDuckGo answer in the OpenSearch format, so only the
+// second element of the array is needed
+#[cfg(feature = "server")]
+async fn fetch_upstream(url: &str) -> Result<Vec<String>, reqwest::Error> {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    let client = CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .expect("failed to build HTTP client")
+    });
+
+    let body: serde_json::Value = client.get(url).send().await?.json().await?;
+    let suggestions = body
+        .get(1)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(suggestions)
+}
+
+// Complete a partially typed bang against the bangs visible to the caller
+#[cfg(feature = "server")]
+fn bang_completions<'a>(
+    prefix: &str,
+    config: &'a Config,
+    is_authenticated: bool,
+) -> Vec<(&'a String, &'a BangDetails)> {
+    let mut matches: Vec<_> = config
+        .bangs
+        .iter()
+        .chain(config.private_bangs.iter().filter(|_| is_authenticated))
+        .filter(|(key, _)| key.starts_with(prefix))
+        .collect();
+
+    // Shortest keys first so an exact match is always on top
+    matches.sort_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.0.cmp(b.0)));
+    matches.truncate(MAX_SUGGESTIONS);
+    matches
+}
+
+// Build the suggestion list for a query, mixing bang and upstream completions
+#[cfg(feature = "server")]
+pub async fn suggest(query: &str, config: &Config, is_authenticated: bool) -> Suggestions {
+    let trimmed = query.trim_start();
+    let mut completions = Vec::new();
+    let mut descriptions = Vec::new();
+
+    // A bang that is still being typed only gets bang completions
+    if trimmed.starts_with('!') && !trimmed.contains(' ') {
+        for (key, details) in bang_completions(trimmed, config, is_authenticated) {
+            completions.push(format!("{} ", key));
+            descriptions.push(details.name.clone());
+        }
+        let urls = vec![String::new(); completions.len()];
+        return Suggestions(query.to_string(), completions, descriptions, urls);
+    }
+
+    // Keep a leading bang on every upstream completion
+    let (prefix, term) = match trimmed.split_once(' ') {
+        Some((bang, rest)) if bang.starts_with('!') => (format!("{} ", bang), rest),
+        _ => (String::new(), trimmed),
+    };
+
+    if !term.trim().is_empty() {
+        for suggestion in config.default_engine.suggest.fetch(term).await {
+            completions.push(format!("{}{}", prefix, suggestion));
+            descriptions.push(String::new());
+            if completions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+    }
+
+    let urls = vec![String::new(); completions.len()];
+    Suggestions(query.to_string(), completions, descriptions, urls)
+}
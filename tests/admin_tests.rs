@@ -0,0 +1,216 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    admin::preview_target,
+    config::{default_config, load_config, BangDetails, Config},
+    state::AppState,
+};
+use common::{base_config, read, send};
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.admin.token = "admin_token".to_string();
+    config
+}
+
+// Call the admin API with a bearer token and an optional JSON body
+async fn call(state: &AppState, method: &str, uri: &str, token: &str, body: Option<Value>) -> Response {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json");
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty);
+    send(state, request.body(body).unwrap()).await
+}
+
+// Load a config file written to `dir`, with the admin overlay next to it
+async fn file_config(dir: &Path, contents: &str) -> Config {
+    let path = dir.join("config.yml");
+    std::fs::write(&path, contents).unwrap();
+    let mut config = load_config(path.to_str().unwrap()).await.unwrap();
+    config.auth_token = "secret_token".to_string();
+    config.admin.token = "admin_token".to_string();
+    config.admin.overlay = Some(dir.join("bangs.overlay.yml").to_str().unwrap().to_string());
+    config
+}
+
+fn location(response: &Response) -> &str {
+    response.headers().get(header::LOCATION).unwrap().to_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_token_is_separate() {
+    let state = AppState::new(test_config()).unwrap();
+
+    assert_eq!(call(&state, "GET", "/admin/bangs", "admin_token", None).await.status(), StatusCode::OK);
+    assert_eq!(call(&state, "GET", "/admin/bangs", "secret_token", None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(call(&state, "GET", "/admin/bangs", "wrong", None).await.status(), StatusCode::UNAUTHORIZED);
+
+    // The API is disabled without an admin token
+    let state = AppState::new(default_config()).unwrap();
+    assert_eq!(call(&state, "GET", "/admin/bangs", "", None).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_changes_apply_live() {
+    let state = AppState::new(test_config()).unwrap();
+
+    let bang = json!({"name": "!rs", "bang": {"url": "https://docs.rs/releases/search?query={searchTerms}", "name": "Docs.rs"}});
+    let response = call(&state, "POST", "/admin/bangs", "admin_token", Some(bang)).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = call(&state, "GET", "/search?q=!rs%20serde", "", None).await;
+    assert_eq!(location(&response), "https://docs.rs/releases/search?query=serde");
+
+    // Rename and make private in one update
+    let update = json!({"name": "!rust", "private": true});
+    let response = call(&state, "PUT", "/admin/bangs/!rs", "admin_token", Some(update)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = call(&state, "GET", "/search?q=!rust%20serde&login=secret_token", "", None).await;
+    assert_eq!(location(&response), "https://docs.rs/releases/search?query=serde");
+    let response = call(&state, "GET", "/search?q=!rust%20serde", "", None).await;
+    assert!(location(&response).starts_with("https://www.google.com/"));
+
+    let response = call(&state, "DELETE", "/admin/bangs/!rust", "admin_token", None).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = call(&state, "GET", "/admin/bangs/!rust", "admin_token", None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_validation() {
+    let state = AppState::new(test_config()).unwrap();
+
+    let cases = [
+        (json!({"name": "rs", "bang": {"url": "https://docs.rs/?q={searchTerms}"}}), StatusCode::BAD_REQUEST),
+        (json!({"name": "!rs", "bang": {"url": "not a url"}}), StatusCode::BAD_REQUEST),
+        (json!({"name": "!rs", "bang": {"url": "https://docs.rs/", "form": {"q": "{searchTerms}"}}}), StatusCode::BAD_REQUEST),
+        (json!({"name": "!g", "bang": {"url": "https://docs.rs/?q={searchTerms}"}}), StatusCode::CONFLICT),
+        (json!({"name": "!rs"}), StatusCode::BAD_REQUEST),
+    ];
+    for (body, status) in cases {
+        let response = call(&state, "POST", "/admin/bangs", "admin_token", Some(body.clone())).await;
+        assert_eq!(response.status(), status, "{}", body);
+    }
+
+    // Renaming onto an existing bang is refused
+    let response = call(&state, "PUT", "/admin/bangs/!yt", "admin_token", Some(json!({"name": "!g"}))).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    // Config files go through the same checks
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yml");
+    std::fs::write(&path, "bangs:\n  \"g\":\n    url: \"https://www.google.com/search?q={searchTerms}\"\n").unwrap();
+    let error = load_config(path.to_str().unwrap()).await.unwrap_err();
+    assert!(error.to_string().contains("Invalid bang name 'g'"));
+}
+
+#[tokio::test]
+async fn test_overlay_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let overlay = dir.path().join("data/bangs.overlay.yml");
+    let mut config = test_config();
+    config.admin.overlay = Some(overlay.to_str().unwrap().to_string());
+
+    let state = AppState::new(config.clone()).unwrap();
+    let bang = json!({"name": "!rs", "private": true, "bang": {"url": "https://docs.rs/?q={searchTerms}"}});
+    call(&state, "POST", "/admin/bangs", "admin_token", Some(bang)).await;
+    call(&state, "DELETE", "/admin/bangs/!yt", "admin_token", None).await;
+
+    assert!(overlay.exists());
+    assert!(!dir.path().join("data/bangs.overlay.yml.tmp").exists());
+
+    let restarted = AppState::new(config).unwrap();
+    let (_, _, body) = read(call(&restarted, "GET", "/admin/bangs", "admin_token", None).await).await;
+    let bangs: Value = serde_json::from_str(&body).unwrap();
+    let names: Vec<&str> = bangs.as_array().unwrap().iter().map(|b| b["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"!rs"));
+    assert!(!names.contains(&"!yt"));
+    assert_eq!(bangs.as_array().unwrap().iter().find(|b| b["name"] == "!rs").unwrap()["private"], true);
+}
+
+#[tokio::test]
+async fn test_interpolated_secrets_stay_out_of_the_overlay() {
+    std::env::set_var("ADMIN_TEST_WIKI_KEY", "s3cr3t");
+    std::env::set_var("ADMIN_TEST_OTHER_SECRET", "0th3r");
+    let dir = tempfile::tempdir().unwrap();
+    let raw = "https://wiki.example.com/search?key=${ADMIN_TEST_WIKI_KEY}&q={searchTerms}";
+    let state = AppState::new(file_config(dir.path(), &format!("bangs:\n  \"!wiki\":\n    url: \"{}\"\n", raw)).await).unwrap();
+    let overlay = dir.path().join("bangs.overlay.yml");
+
+    // A rename without a body copies the bang as written
+    let response = call(&state, "PUT", "/admin/bangs/!wiki", "admin_token", Some(json!({"name": "!kb"}))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved = std::fs::read_to_string(&overlay).unwrap();
+    assert!(saved.contains("${ADMIN_TEST_WIKI_KEY}") && !saved.contains("s3cr3t"), "{}", saved);
+
+    let (_, _, body) = read(call(&state, "GET", "/admin/bangs/!kb", "admin_token", None).await).await;
+    assert!(!body.contains("s3cr3t"), "{}", body);
+    let (_, _, body) = read(call(&state, "GET", "/bangs?q=kb", "", None).await).await;
+    assert!(body.contains("${ADMIN_TEST_WIKI_KEY}") && !body.contains("s3cr3t"), "{}", body);
+    let bang = BangDetails { url: raw.to_string(), ..Default::default() };
+    let preview = preview_target(&state.config(), &bang, "roadmap").unwrap();
+    assert_eq!(preview, "https://wiki.example.com/search?key=${ADMIN_TEST_WIKI_KEY}&q=roadmap");
+
+    // Searches still get the secret filled in
+    let response = call(&state, "GET", "/search?q=!kb%20roadmap", "", None).await;
+    assert_eq!(location(&response), "https://wiki.example.com/search?key=s3cr3t&q=roadmap");
+
+    // The admin API can only reuse references written like config.yml's
+    let leaks = [
+        "https://evil.example.com/?k=${ADMIN_TEST_OTHER_SECRET}&q={searchTerms}",
+        "https://evil.example.com/?k=${file:/etc/hostname}&q={searchTerms}",
+    ];
+    for url in leaks {
+        let response = call(&state, "POST", "/admin/bangs", "admin_token", Some(json!({"name": "!leak", "bang": {"url": url}}))).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bang = BangDetails { url: url.to_string(), ..Default::default() };
+        assert!(preview_target(&state.config(), &bang, "x").is_err());
+    }
+    assert!(!std::fs::read_to_string(&overlay).unwrap().contains("!leak"));
+}
+
+#[tokio::test]
+async fn test_renames_keep_every_value_as_written() {
+    std::env::set_var("ADMIN_TEST_INTRANET_HOST", "intranet.internal");
+    std::env::set_var("ADMIN_TEST_INTRANET_KEY", "k3y");
+    let dir = tempfile::tempdir().unwrap();
+    let config = file_config(dir.path(), concat!(
+        "bangs:\n",
+        "  \"!in\":\n",
+        "    url: \"https://search.example.com/\"\n",
+        "    home_url: \"https://${ADMIN_TEST_INTRANET_HOST}/\"\n",
+        "    method: post\n",
+        "    form:\n",
+        "      q: \"{searchTerms}\"\n",
+        "      key: \"${ADMIN_TEST_INTRANET_KEY}\"\n",
+    )).await;
+    let overlay = dir.path().join("bangs.overlay.yml");
+    let state = AppState::new(config).unwrap();
+
+    // Renaming and making private without a body copies the bang as written
+    let update = json!({"name": "!intra", "private": true});
+    let response = call(&state, "PUT", "/admin/bangs/!in", "admin_token", Some(update)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved = std::fs::read_to_string(&overlay).unwrap();
+    assert!(saved.contains("${ADMIN_TEST_INTRANET_HOST}") && saved.contains("${ADMIN_TEST_INTRANET_KEY}"), "{}", saved);
+    assert!(!saved.contains("intranet.internal") && !saved.contains("k3y"), "{}", saved);
+
+    let (_, _, body) = read(call(&state, "GET", "/admin/bangs/!intra", "admin_token", None).await).await;
+    assert!(!body.contains("intranet.internal") && !body.contains("k3y"), "{}", body);
+    let (_, _, body) = read(call(&state, "GET", "/admin/bangs", "admin_token", None).await).await;
+    assert!(!body.contains("intranet.internal") && !body.contains("k3y"), "{}", body);
+
+    // The renamed bang still gets the values filled in
+    let response = call(&state, "GET", "/search?q=!intra&login=secret_token", "", None).await;
+    assert_eq!(location(&response), "https://intranet.internal/");
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    admin::preview_target,
+    auth::admin_session,
+    config::{BangDetails, BangMode, Config},
+    state::AppState,
+};
+use common::{base_config, read, send};
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.admin.token = "admin_token".to_string();
+    config.private_bangs.insert("!wiki".to_string(), BangDetails {
+        url: "https://wiki.example.com/search?q={searchTerms}".to_string(),
+        name: "Wiki".to_string(),
+        ..Default::default()
+    });
+    config
+}
+
+async fn login(state: &AppState, token: &str) -> Response {
+    let request = Request::post("/admin/login")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(format!("token={}", token)))
+        .unwrap();
+    send(state, request).await
+}
+
+// Log in and return the session cookie
+async fn session(state: &AppState) -> String {
+    let response = login(state, "admin_token").await;
+    let cookie = response.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap();
+    cookie.split(';').next().unwrap().to_string()
+}
+
+async fn get_page(state: &AppState, uri: &str, cookie: &str) -> (StatusCode, String) {
+    let request = Request::get(uri).header(header::COOKIE, cookie).body(Body::empty()).unwrap();
+    let (status, _, page) = read(send(state, request).await).await;
+    (status, page)
+}
+
+async fn post_form(state: &AppState, uri: &str, cookie: &str, form: &str) -> Response {
+    let request = Request::post(uri)
+        .header(header::COOKIE, cookie)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(form.to_string()))
+        .unwrap();
+    send(state, request).await
+}
+
+#[tokio::test]
+async fn test_login() {
+    let state = AppState::new(test_config()).unwrap();
+
+    let (status, page) = get_page(&state, "/admin", "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains(r#"action="/admin/login""#));
+    assert!(!page.contains("!wiki"));
+
+    // The search auth_token is not an admin credential
+    assert_eq!(login(&state, "secret_token").await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(get_page(&state, "/admin", "bang_admin=forged").await.0, StatusCode::OK);
+
+    let response = login(&state, "admin_token").await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let cookie = response.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(cookie.contains("HttpOnly"));
+    assert!(cookie.contains("SameSite=Strict"));
+    assert!(!cookie.contains("admin_token"));
+
+    assert!(cookie.contains("Max-Age=43200"));
+
+    let cookie = session(&state).await;
+    let (_, page) = get_page(&state, "/admin", &cookie).await;
+    assert!(page.contains("Private bangs"));
+    assert!(page.contains("<code>!wiki</code>"));
+
+    // Sessions expire, and the issue time can't be changed without the token
+    let expired = format!("bang_admin={}", admin_session(&state.config(), 0).unwrap());
+    assert!(get_page(&state, "/admin", &expired).await.1.contains(r#"action="/admin/login""#));
+    let (_, signature) = cookie.split_once('.').unwrap();
+    let moved = format!("bang_admin=9999999999.{}", signature);
+    assert!(get_page(&state, "/admin", &moved).await.1.contains(r#"action="/admin/login""#));
+}
+
+#[tokio::test]
+async fn test_try_it_previews() {
+    let state = AppState::new(test_config()).unwrap();
+    let cookie = session(&state).await;
+
+    let (_, page) = get_page(&state, "/admin?sample=rust%20lang", &cookie).await;
+    assert!(page.contains(r#"<a href="https://www.google.com/search?q=rust%20lang" rel="noreferrer">"#));
+    assert!(page.contains(r#"<a href="https://wiki.example.com/search?q=rust%20lang" rel="noreferrer">"#));
+
+    // Testing an unsaved bang previews it without adding it
+    let form = "key=%21docs&url=https%3A%2F%2Fdocs.rs%2F%3Fq%3D%7BsearchTerms%7D&sample=serde&action=test";
+    let (status, _, page) = read(post_form(&state, "/admin/save", &cookie, form).await).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Try it: <a href=\"https://docs.rs/?q=serde\""));
+    assert!(!page.contains("<code>!docs</code>"));
+
+    let mut config = test_config();
+    config.redirect_policy.allow_hosts = vec!["example.com".to_string()];
+    let bang = &config.bangs["!g"];
+    assert_eq!(preview_target(&config, bang, "rust").unwrap_err(), "'www.google.com' is not an allowed destination");
+    let go = BangDetails { url: "https://{searchTerms}".to_string(), mode: BangMode::Navigate, ..Default::default() };
+    assert_eq!(preview_target(&config, &go, "wiki.example.com").unwrap(), "https://wiki.example.com/");
+}
+
+#[tokio::test]
+async fn test_add_edit_and_delete() {
+    let state = AppState::new(test_config()).unwrap();
+    let cookie = session(&state).await;
+
+    let form = "key=%21docs&name=Docs.rs&url=https%3A%2F%2Fdocs.rs%2F%3Fq%3D%7BsearchTerms%7D&mode=search&sample=x&action=save";
+    let response = post_form(&state, "/admin/save", &cookie, form).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/admin?sample=x&edit=%21docs");
+
+    // The edit form is filled in from the saved bang
+    let (_, page) = get_page(&state, "/admin?edit=%21docs", &cookie).await;
+    assert!(page.contains("Edit !docs"));
+    assert!(page.contains(r#"name="url" value="https://docs.rs/?q={searchTerms}""#));
+
+    // Rename and make private
+    let form = "original=%21docs&key=%21rs&name=Docs.rs&url=https%3A%2F%2Fdocs.rs%2F%3Fq%3D%7BsearchTerms%7D&private=on&action=save";
+    assert_eq!(post_form(&state, "/admin/save", &cookie, form).await.status(), StatusCode::SEE_OTHER);
+    let config = state.config();
+    assert!(config.private_bangs.contains_key("!rs"));
+    assert!(!config.bangs.contains_key("!docs"));
+
+    let response = post_form(&state, "/admin/delete", &cookie, "name=%21rs").await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert!(!state.config().private_bangs.contains_key("!rs"));
+}
+
+#[tokio::test]
+async fn test_errors_and_sessions() {
+    let state = AppState::new(test_config()).unwrap();
+    let cookie = session(&state).await;
+
+    let form = "key=docs&url=https%3A%2F%2Fdocs.rs%2F&action=save";
+    let (status, _, page) = read(post_form(&state, "/admin/save", &cookie, form).await).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(page.contains("Invalid bang name &#39;docs&#39;"));
+    // The form keeps what was typed
+    assert!(page.contains(r#"name="key" value="docs""#));
+
+    let form = "key=%21g&url=https%3A%2F%2Fdocs.rs%2F&action=save";
+    assert_eq!(post_form(&state, "/admin/save", &cookie, form).await.status(), StatusCode::CONFLICT);
+
+    // Changes need a session
+    let response = post_form(&state, "/admin/delete", "", "name=%21g").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(state.config().bangs.contains_key("!g"));
+
+    // Logging out clears the cookie
+    let response = post_form(&state, "/admin/logout", &cookie, "").await;
+    let cleared = response.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(cleared.starts_with("bang_admin=;"));
+    assert!(cleared.contains("Max-Age=0"));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use axum::http::StatusCode;
+use bang_search::{
+    config::{read_config, BangDetails, Config},
+    export::{export, ExportFormat},
+};
+use common::{base_config, json, keys, read};
+use serde_json::Value;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.host_url = Some("https://search.example.com".to_string());
+    let gh = config.bangs.get_mut("!gh").unwrap();
+    gh.description = Some("Search code on GitHub".to_string());
+    gh.category = Some("Development".to_string());
+    gh.tags = vec!["code".to_string(), "git".to_string()];
+    gh.examples = vec!["!gh ripgrep".to_string()];
+    config.bangs.get_mut("!ddg").unwrap().deprecated = true;
+    config.private_bangs.insert("!wiki".to_string(), BangDetails {
+        url: "https://wiki.example.com/search?q={searchTerms}".to_string(),
+        name: "Team Wiki".to_string(),
+        category: Some("Development".to_string()),
+        ..Default::default()
+    });
+    config
+}
+
+async fn get(uri: &str) -> (StatusCode, String) {
+    let (status, _, body) = read(common::get(test_config(), uri).await).await;
+    (status, body)
+}
+
+async fn get_json(uri: &str) -> Value {
+    json(common::get(test_config(), uri).await).await
+}
+
+#[tokio::test]
+async fn test_listing_metadata_and_filters() {
+    let list = get_json("/bangs?category=development&login=secret_token").await;
+    assert_eq!(keys(&list), vec!["!gh", "!wiki"]);
+    let gh = &list["bangs"][0];
+    assert_eq!(gh["description"], "Search code on GitHub");
+    assert_eq!(gh["tags"], serde_json::json!(["code", "git"]));
+    assert_eq!(gh["examples"], serde_json::json!(["!gh ripgrep"]));
+    assert_eq!(gh["visibility"], "public");
+    assert_eq!(gh["deprecated"], false);
+
+    // Visibility filters independently of the category, and q searches tags
+    let list = get_json("/bangs?category=development&visibility=public&login=secret_token").await;
+    assert_eq!(keys(&list), vec!["!gh"]);
+    let list = get_json("/bangs?q=git&fields=key").await;
+    assert_eq!(keys(&list), vec!["!gh"]);
+
+    let list = get_json("/bangs?q=duck").await;
+    assert_eq!(list["bangs"][0]["deprecated"], true);
+}
+
+#[tokio::test]
+async fn test_live_metadata() {
+    let live = get_json("/live?q=!gh%20ripgrep").await;
+    assert_eq!(live["bang"], "!gh");
+    assert_eq!(live["bang_description"], "Search code on GitHub");
+    assert_eq!(live["bang_category"], "Development");
+    assert_eq!(live["bang_tags"], serde_json::json!(["code", "git"]));
+    assert_eq!(live["bang_deprecated"], false);
+
+    let live = get_json("/live?q=!yt%20cats").await;
+    assert_eq!(live["bang_description"], Value::Null);
+    assert_eq!(live["bang_tags"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_opensearch_and_bookmarks_use_description() {
+    let (status, xml) = get("/export/opensearch/gh").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.contains("<Description>Search code on GitHub</Description>"), "{}", xml);
+    assert!(xml.contains("<Tags>Development code git</Tags>"), "{}", xml);
+
+    // Without a description the generated one is kept
+    let (_, xml) = get("/export/opensearch/yt").await;
+    assert!(xml.contains("<Description>Search YouTube (!yt)</Description>"), "{}", xml);
+
+    let html = export(&test_config(), ExportFormat::Bookmarks, false);
+    assert!(html.contains("SHORTCUTURL=\"gh\">GitHub</A>\n        <DD>Search code on GitHub\n"), "{}", html);
+}
+
+#[test]
+fn test_metadata_is_optional() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yml");
+    std::fs::write(&path, "bangs:\n  \"!g\":\n    url: \"https://www.google.com/search?q={searchTerms}\"\n").unwrap();
+    let config = read_config(path.to_str().unwrap()).unwrap();
+    let g = &config.bangs["!g"];
+    assert!(g.description.is_none() && g.category.is_none() && g.tags.is_empty() && !g.deprecated);
+
+    // Unset metadata is left out when serialized, so existing ETags don't change
+    let yaml = serde_yaml::to_string(&config.bangs["!g"]).unwrap();
+    assert!(!yaml.contains("tags") && !yaml.contains("deprecated"), "{}", yaml);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    config::{BangDetails, Config},
+    listing::MAX_LIMIT,
+    state::AppState,
+};
+use common::{base_config, json, keys, send};
+use serde_json::Value;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.private_bangs.insert("!private".to_string(), BangDetails {
+        url: "https://private.example.com/search?q={searchTerms}".to_string(),
+        name: "Private Search".to_string(),
+        icon: "private".to_string(),
+        category: Some("internal".to_string()),
+        ..Default::default()
+    });
+    config
+}
+
+async fn get(config: Config, uri: &str, if_none_match: Option<&str>) -> Response {
+    let mut request = Request::get(uri);
+    if let Some(etag) = if_none_match {
+        request = request.header("if-none-match", etag);
+    }
+    send(&AppState::new(config).unwrap(), request.body(Body::empty()).unwrap()).await
+}
+
+async fn get_json(uri: &str) -> Value {
+    json(get(test_config(), uri, None).await).await
+}
+
+#[tokio::test]
+async fn test_search_over_key_and_name() {
+    let list = get_json("/bangs?q=goo").await;
+    assert_eq!(keys(&list), vec!["!g", "!maps"]);
+
+    let list = get_json("/bangs?q=!yt").await;
+    assert_eq!(keys(&list), vec!["!yt"]);
+}
+
+#[tokio::test]
+async fn test_category_filter() {
+    let list = get_json("/bangs?category=Internal&login=secret_token").await;
+    assert_eq!(keys(&list), vec!["!private"]);
+
+    let list = get_json("/bangs?category=internal").await;
+    assert_eq!(list["total"], 0);
+
+    // Bangs without a category have none, rather than their visibility
+    let list = get_json("/bangs?category=public").await;
+    assert_eq!(list["total"], 0);
+    assert_eq!(get_json("/bangs?q=!yt").await["bangs"][0]["category"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_pagination_and_fields() {
+    let list = get_json("/bangs?limit=2&offset=1&fields=key,name").await;
+
+    assert_eq!(list["total"], 6);
+    assert_eq!(list["offset"], 1);
+    assert_eq!(list["next_offset"], 3);
+    assert_eq!(list["bangs"][0], serde_json::json!({ "key": "!g", "name": "Google" }));
+    assert_eq!(keys(&list), vec!["!g", "!gh"]);
+
+    let last = get_json("/bangs?limit=2&offset=4").await;
+    assert_eq!(last["next_offset"], Value::Null);
+
+    // A zero limit still moves through the list, and large ones are capped
+    let first = get_json("/bangs?limit=0").await;
+    assert_eq!(keys(&first).len(), 1);
+    assert_eq!(first["next_offset"], 1);
+    let mut large = test_config();
+    for i in 0..MAX_LIMIT {
+        large.bangs.insert(format!("!b{}", i), large.bangs["!g"].clone());
+    }
+    let page = json(get(large, &format!("/bangs?limit={}", MAX_LIMIT + 100), None).await).await;
+    assert_eq!(keys(&page).len(), MAX_LIMIT);
+    assert_eq!(page["next_offset"], MAX_LIMIT);
+
+    let response = get(test_config(), "/bangs?fields=key,secret", None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_etag_revalidation() {
+    let response = get(test_config(), "/bangs", None).await;
+    let etag = response.headers().get("etag").unwrap().to_str().unwrap().to_string();
+
+    let response = get(test_config(), "/bangs", Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // A different view or a changed config gets a new tag
+    let response = get(test_config(), "/bangs?login=secret_token", Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut changed = test_config();
+    changed.bangs.remove("!w");
+    let response = get(changed, "/bangs", Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // So does a bang moving to another file, which the listing shows too
+    let mut moved = test_config();
+    moved.bangs.get_mut("!w").unwrap().source = Some("config.d/wiki.yml".to_string());
+    let response = get(moved, "/bangs", Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Only the bangs go into the tag, not secrets such as the auth_token
+    let mut rotated = test_config();
+    rotated.auth_token = "other_token".to_string();
+    let response = get(rotated, "/bangs", Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    auth::user_credential,
+    bookmarks::{fuzzy_matches, parse_command, BookmarkCommand},
+    config::UserConfig,
+    state::AppState,
+    storage::Storage,
+};
+use common::{base_config, json, read, search_uri, send};
+
+fn test_state() -> AppState {
+    let mut config = base_config();
+    config.users.insert("alice".to_string(), UserConfig {
+        id: "a1b2c3".to_string(),
+        revoked: false,
+    });
+    AppState::new(config).unwrap()
+}
+
+async fn search(state: &AppState, query: &str, login: &str) -> Response {
+    let uri = format!("{}&login={}", search_uri(query), login);
+    send(state, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(parse_command("!b", "docs"), Some(BookmarkCommand::Open("docs")));
+    assert_eq!(parse_command("!ba", "docs https://docs.rs"), Some(BookmarkCommand::Add { name: "docs", url: "https://docs.rs" }));
+    assert_eq!(parse_command("!bd", " docs "), Some(BookmarkCommand::Delete("docs")));
+    assert_eq!(parse_command("!g", "docs"), None);
+}
+
+#[test]
+fn test_store_and_fuzzy_match() {
+    let storage = Storage::in_memory().unwrap();
+    storage.add_bookmark("owner", "rust-docs", "docs.rs").unwrap();
+    storage.add_bookmark("owner", "rustup", "https://rustup.rs").unwrap();
+    storage.add_bookmark("alice", "mail", "https://mail.example.com").unwrap();
+
+    // Missing schemes default to https, and owners don't see each other's bookmarks
+    let bookmarks = storage.list_bookmarks("owner").unwrap();
+    assert_eq!(bookmarks.len(), 2);
+    assert_eq!(bookmarks[0].url, "https://docs.rs/");
+
+    let names: Vec<String> = fuzzy_matches(bookmarks, "rdocs").into_iter().map(|b| b.name).collect();
+    assert_eq!(names, vec!["rust-docs"]);
+
+    assert!(storage.add_bookmark("owner", "bad", "javascript:alert(1)").is_err());
+    assert!(storage.delete_bookmark("owner", "RUSTUP").unwrap());
+    assert!(storage.get_bookmark("owner", "rustup").unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_bookmark_bangs() {
+    let state = test_state();
+
+    let response = search(&state, "!ba docs https://docs.rs", "secret_token").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = search(&state, "!b docs", "secret_token").await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers().get("location").unwrap(), "https://docs.rs/");
+
+    // Partial names get a list of candidates instead of a redirect
+    let (status, _, html) = read(search(&state, "!b doc", "secret_token").await).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains(r#"<a href="https://docs.rs/">docs</a>"#));
+
+    search(&state, "!bd docs", "secret_token").await;
+    assert!(state.storage.list_bookmarks("owner").unwrap().is_empty());
+
+    // Without a login the bangs are not handled natively
+    let response = search(&state, "!b docs", "wrong").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://www.google.com/search?q=%21b%20docs");
+}
+
+#[tokio::test]
+async fn test_bookmarks_api() {
+    let state = test_state();
+
+    let create = Request::post("/api/bookmarks?login=secret_token")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"name": "news", "url": "https://news.ycombinator.com"}"#))
+        .unwrap();
+    assert_eq!(send(&state, create).await.status(), StatusCode::CREATED);
+
+    let update = Request::put("/api/bookmarks/news?login=secret_token")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"name": "hn"}"#))
+        .unwrap();
+    assert_eq!(send(&state, update).await.status(), StatusCode::OK);
+
+    // Renaming onto another bookmark conflicts instead of replacing it
+    state.storage.add_bookmark("owner", "lobsters", "https://lobste.rs").unwrap();
+    let rename = Request::put("/api/bookmarks/lobsters?login=secret_token")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"name": "HN"}"#))
+        .unwrap();
+    assert_eq!(send(&state, rename).await.status(), StatusCode::CONFLICT);
+
+    let response = send(&state, Request::get("/api/bookmarks?login=secret_token").body(Body::empty()).unwrap()).await;
+    let list = json(response).await;
+    assert_eq!(list[0]["name"], "hn");
+    assert_eq!(list[0]["url"], "https://news.ycombinator.com/");
+    assert_eq!(list[1]["name"], "lobsters");
+
+    let delete = Request::delete("/api/bookmarks/hn?login=secret_token").body(Body::empty()).unwrap();
+    assert_eq!(send(&state, delete).await.status(), StatusCode::NO_CONTENT);
+
+    let anonymous = Request::get("/api/bookmarks").body(Body::empty()).unwrap();
+    assert_eq!(send(&state, anonymous).await.status(), StatusCode::UNAUTHORIZED);
+
+    // Users manage the same bookmarks through the API as with !ba and !bd,
+    // and only their own
+    let login = user_credential(&state.config(), "a1b2c3");
+    assert_eq!(search(&state, "!ba mail https://mail.example.com", &login).await.status(), StatusCode::OK);
+    let response = send(&state, Request::get(format!("/api/bookmarks?login={}", login)).body(Body::empty()).unwrap()).await;
+    let list = json(response).await;
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["name"], "mail");
+    let delete = Request::delete(format!("/api/bookmarks/lobsters?login={}", login)).body(Body::empty()).unwrap();
+    assert_eq!(send(&state, delete).await.status(), StatusCode::NOT_FOUND);
+    let delete = Request::delete(format!("/api/bookmarks/mail?login={}", login)).body(Body::empty()).unwrap();
+    assert_eq!(send(&state, delete).await.status(), StatusCode::NO_CONTENT);
+    assert!(state.storage.list_bookmarks("alice").unwrap().is_empty());
+    assert_eq!(state.storage.list_bookmarks("owner").unwrap().len(), 1);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use bang_search::{
+    cases::{run_case, CaseFile, MissingExpectation, TestCase},
+    config::{default_config, read_config},
+};
+use common::set_auth_token;
+use std::process::Command;
+
+fn case(query: &str) -> TestCase {
+    TestCase {
+        name: None,
+        query: query.to_string(),
+        user: None,
+        url: None,
+        fallback: false,
+    }
+}
+
+#[test]
+fn test_project_config_cases() {
+    set_auth_token();
+    let path = "tests/cases/bang_cases.yml";
+    let file = CaseFile::load(path).unwrap();
+    let config = read_config(file.config_path(path).unwrap().to_str().unwrap()).unwrap();
+
+    for case in &file.cases {
+        if let Err(failure) = run_case(&config, case) {
+            panic!("{}", failure);
+        }
+    }
+}
+
+#[test]
+fn test_failures_show_a_diff() {
+    let mut config = default_config();
+    // A template that lost its {searchTerms}
+    config.bangs.get_mut("!yt").unwrap().url = "https://www.youtube.com/results".to_string();
+
+    let yt = TestCase {
+        url: Some("https://www.youtube.com/results?search_query=lofi".to_string()),
+        ..case("!yt lofi")
+    };
+    let failure = run_case(&config, &yt).unwrap_err();
+    assert_eq!(failure.to_string(), concat!(
+        "FAIL !yt lofi\n",
+        "  expected: https://www.youtube.com/results?search_query=lofi\n",
+        "  actual:   https://www.youtube.com/results [public bang !yt]",
+    ));
+
+    let fallback = TestCase { fallback: true, ..case("!g lofi") };
+    assert_eq!(run_case(&config, &fallback).unwrap_err().expected, "fallback to the default engine");
+
+    let unknown_user = TestCase { fallback: true, user: Some("mallory".to_string()), ..case("!g lofi") };
+    assert_eq!(run_case(&config, &unknown_user).unwrap_err().actual, "no such user");
+}
+
+#[test]
+fn test_cases_need_an_expectation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cases.yml");
+    std::fs::write(&path, "cases:\n  - query: \"!g rust\"\n").unwrap();
+
+    let error = CaseFile::load(path.to_str().unwrap()).unwrap_err();
+    assert_eq!(error.downcast_ref::<MissingExpectation>(), Some(&MissingExpectation("!g rust".to_string())));
+}
+
+#[test]
+fn test_command() {
+    let output = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["test", "tests/cases/private_bangs.yml"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5 passed, 0 failed\n");
+
+    // Against a config without private bangs, the cases that use one fail
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("public.yml");
+    std::fs::write(&config, "bangs:\n  \"!g\":\n    url: \"https://www.google.com/search?q={searchTerms}\"\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["test", "tests/cases/private_bangs.yml", "--config", config.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("FAIL private bang with auth (as owner)"));
+    assert!(stdout.ends_with("3 passed, 2 failed\n"));
+
+    // A config that doesn't exist is an error, not the defaults
+    let output = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["test", "tests/cases/private_bangs.yml", "--config", "missing.yml"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.yml"));
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use bang_search::config::read_config;
+use common::{get, json};
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"
+bangs:
+  "!gh":
+    url: "https://github.com/search?q={searchTerms}"
+    name: "GitHub"
+  "!js":
+    url: "javascript:alert('{searchTerms}')"
+    name: "Script"
+private_bangs:
+  "!wiki":
+    url: "https://wiki.example.com/search?q={searchTerms}"
+    name: "Wiki"
+auth_token: "secret_token"
+users:
+  alice:
+    id: "a1b2c3"
+  bob:
+    id: "d4e5f6"
+    revoked: true
+host_url: "https://search.example.com"
+"#;
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    let config = dir.join("config.yml");
+    std::fs::write(&config, CONFIG).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(args)
+        .arg("--config")
+        .arg(&config)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_resolve() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(dir.path(), &["resolve", "!gh serde json"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains("Matched: bang !gh"));
+    assert!(text.contains("Term:    serde json"));
+    assert!(text.contains("URL:     https://github.com/search?q=serde%20json"));
+
+    // Blocked redirects are reported and fail
+    let output = run(dir.path(), &["resolve", "!js hi"]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("Blocked: javascript:alert('hi') ('javascript:' links are not allowed)"));
+}
+
+#[test]
+fn test_resolve_json_as_user() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(dir.path(), &["resolve", "roadmap !wiki", "--user", "alice", "--json"]);
+    assert!(output.status.success());
+    let json: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["matched"]["type"], "bang");
+    assert_eq!(json["matched"]["key"], "!wiki");
+    assert_eq!(json["matched"]["private"], true);
+    assert_eq!(json["term"], "roadmap");
+    assert_eq!(json["url"], "https://wiki.example.com/search?q=roadmap");
+
+    // Without a user the private bang is not visible
+    let output = run(dir.path(), &["resolve", "roadmap !wiki", "--json"]);
+    let json: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["matched"]["type"], "default");
+
+    let output = run(dir.path(), &["resolve", "roadmap !wiki", "--user", "bob"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown or revoked user 'bob'"));
+}
+
+#[tokio::test]
+async fn test_list_matches_endpoint() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = run(dir.path(), &["list", "--user", "owner", "--json", "--fields", "key,category", "--limit", "2"]);
+    assert!(output.status.success());
+    let cli: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    let config = read_config(dir.path().join("config.yml").to_str().unwrap()).unwrap();
+    let http = json(get(config, "/bangs?login=secret_token&fields=key,category&limit=2").await).await;
+
+    assert_eq!(cli, http);
+    assert_eq!(cli["total"], 3);
+}
+
+#[test]
+fn test_list_text() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(dir.path(), &["list"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains("!gh"));
+    assert!(!text.contains("!wiki"));
+    assert!(text.ends_with("2 of 2 bangs\n"));
+
+    let output = run(dir.path(), &["list", "--fields", "bogus"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown field 'bogus'"));
+}
+
+#[test]
+fn test_serve_refuses_a_broken_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.yml");
+    std::fs::write(&config, "auth_token: \"${CLI_TEST_UNSET_TOKEN}\"\n").unwrap();
+
+    // Without the variable the server must not start on the default config
+    let output = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .env("CONFIG_PATH", &config)
+        .env_remove("CLI_TEST_UNSET_TOKEN")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("'CLI_TEST_UNSET_TOKEN' is not set"), "{}", stderr);
+}
@@ -0,0 +1,71 @@
+// Fixtures and HTTP helpers shared by the integration tests. Every test
+// binary compiles its own copy and only uses some of them.
+#![allow(dead_code)]
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    config::{default_config, Config},
+    routes::create_router,
+    state::AppState,
+};
+use serde_json::Value;
+use std::sync::Once;
+use tower::ServiceExt;
+
+// The default config with "secret_token" as its auth_token
+pub fn base_config() -> Config {
+    let mut config = default_config();
+    config.auth_token = "secret_token".to_string();
+    config
+}
+
+// The shipped config.yml reads auth_token from $AUTH_TOKEN, which has no
+// default. Tests run in parallel, so it is set once per test binary rather
+// than by each test that loads the file.
+pub fn set_auth_token() {
+    static SET: Once = Once::new();
+    SET.call_once(|| std::env::set_var("AUTH_TOKEN", "test_token"));
+}
+
+// Send a request to a router over the given state
+pub async fn send(state: &AppState, request: Request<Body>) -> Response {
+    create_router()
+        .with_state(state.clone())
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+// GET a URI from a server running the given config
+pub async fn get(config: Config, uri: &str) -> Response {
+    let state = AppState::new(config).unwrap();
+    send(&state, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+// The /search URI for a query
+pub fn search_uri(query: &str) -> String {
+    format!("/search?q={}", urlencoding::encode(query))
+}
+
+// Status, headers and body text of a response
+pub async fn read(response: Response) -> (StatusCode, HeaderMap, String) {
+    let (status, headers) = (response.status(), response.headers().clone());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, headers, String::from_utf8(body.to_vec()).unwrap())
+}
+
+// Body of a successful response, parsed as JSON
+pub async fn json(response: Response) -> Value {
+    let (status, _, body) = read(response).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    serde_json::from_str(&body).unwrap()
+}
+
+// Keys of the bangs in a /bangs listing, in order
+pub fn keys(list: &Value) -> Vec<&str> {
+    list["bangs"].as_array().unwrap().iter().map(|b| b["key"].as_str().unwrap()).collect()
+}
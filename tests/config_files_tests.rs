@@ -0,0 +1,104 @@
+mod common;
+
+use bang_search::config::read_config;
+use common::{get, json};
+use std::path::Path;
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+fn bang(key: &str, host: &str) -> String {
+    format!("  \"{}\":\n    url: \"https://{}/?q={{searchTerms}}\"\n", key, host)
+}
+
+#[test]
+fn test_includes_and_fragments_are_merged() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        &dir.path().join("config.yml"),
+        &format!("include:\n  - team.yml\n  - shared\nauth_token: \"secret\"\nbangs:\n{}", bang("!g", "google.com")),
+    );
+    write(&dir.path().join("team.yml"), &format!("private_bangs:\n{}", bang("!wiki", "wiki.example.com")));
+    write(&dir.path().join("shared/docs.yml"), &format!("bangs:\n{}", bang("!rs", "docs.rs")));
+    write(&dir.path().join("config.d/10-personal.yml"), &format!("bangs:\n{}", bang("!me", "example.com")));
+    write(&dir.path().join("config.d/README.txt"), "not a config file");
+
+    let config = read_config(dir.path().join("config.yml").to_str().unwrap()).unwrap();
+    assert_eq!(config.auth_token, "secret");
+
+    let source = |bangs: &std::collections::HashMap<String, bang_search::config::BangDetails>, key: &str| {
+        let source = bangs[key].source.clone().unwrap();
+        Path::new(&source).strip_prefix(dir.path()).unwrap().to_str().unwrap().to_string()
+    };
+    assert_eq!(source(&config.bangs, "!g"), "config.yml");
+    assert_eq!(source(&config.private_bangs, "!wiki"), "team.yml");
+    assert_eq!(source(&config.bangs, "!rs"), "shared/docs.yml");
+    assert_eq!(source(&config.bangs, "!me"), "config.d/10-personal.yml");
+}
+
+#[test]
+fn test_conflicts_name_both_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("config.yml");
+    write(&main, &format!("bangs:\n{}", bang("!g", "google.com")));
+    write(&dir.path().join("config.d/extra.yml"), &format!("bangs:\n{}", bang("!g", "example.com")));
+
+    let error = read_config(main.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("'bangs.!g'"), "{}", error);
+    assert!(error.contains("config.yml") && error.contains("extra.yml"), "{}", error);
+
+    // Plain settings conflict too
+    std::fs::remove_file(dir.path().join("config.d/extra.yml")).unwrap();
+    write(&dir.path().join("config.d/token.yml"), "auth_token: \"a\"\n");
+    write(&dir.path().join("config.d/token2.yml"), "auth_token: \"b\"\n");
+    let error = read_config(main.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("'auth_token'") && error.contains("token.yml") && error.contains("token2.yml"), "{}", error);
+}
+
+#[test]
+fn test_path_lists_and_include_cycles() {
+    let dir = tempfile::tempdir().unwrap();
+    let public = dir.path().join("public/bangs.yml");
+    let personal = dir.path().join("personal/bangs.yml");
+    write(&public, &format!("include:\n  - ../personal/bangs.yml\nbangs:\n{}", bang("!g", "google.com")));
+    write(&personal, &format!("include:\n  - ../public/bangs.yml\nprivate_bangs:\n{}", bang("!me", "example.com")));
+
+    // Both files are listed and include each other; each is merged once
+    let paths = std::env::join_paths([&public, &personal]).unwrap();
+    let config = read_config(paths.to_str().unwrap()).unwrap();
+    assert!(config.bangs.contains_key("!g"));
+    assert!(config.private_bangs.contains_key("!me"));
+
+    // Every file listed must exist
+    let missing = dir.path().join("missing.yml");
+    let paths = std::env::join_paths([&public, &personal, &missing]).unwrap();
+    let error = read_config(paths.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("missing.yml"), "{}", error);
+
+    // An include that does not exist is an error naming it
+    write(&personal, "include:\n  - nowhere.yml\n");
+    let error = read_config(personal.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("nowhere.yml"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_bangs_list_reports_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("config.yml");
+    write(&main, &format!("bangs:\n{}", bang("!g", "google.com")));
+    write(&dir.path().join("config.d/docs.yml"), &format!("bangs:\n{}", bang("!rs", "docs.rs")));
+
+    let config = read_config(main.to_str().unwrap()).unwrap();
+    let list = json(get(config, "/bangs?fields=key,source").await).await;
+    let sources: Vec<(&str, &str)> = list["bangs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| (b["key"].as_str().unwrap(), b["source"].as_str().unwrap()))
+        .collect();
+    assert_eq!(sources.len(), 2);
+    assert!(sources[0].0 == "!g" && sources[0].1.ends_with("config.yml"));
+    assert!(sources[1].0 == "!rs" && sources[1].1.ends_with("config.d/docs.yml"));
+}
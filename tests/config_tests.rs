@@ -22,10 +22,9 @@ async fn test_load_config_nonexistent_file() {
     // Test loading from a file that doesn't exist
     let result = load_config("nonexistent_file.yml").await;
     
-    // Should return default config
-    assert!(result.is_ok());
-    let config = result.unwrap();
-    assert!(config.bangs.contains_key("!g"));
+    // Should fail naming the file rather than quietly using the defaults
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("nonexistent_file.yml"), "{}", error);
 }
 
 #[tokio::test]
@@ -86,4 +85,21 @@ bangs:
     
     // Should return an error
     assert!(result.is_err());
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_bang_home_url() {
+    let config = default_config();
+    
+    // Derived from the template's scheme and host
+    assert_eq!(config.bangs.get("!w").unwrap().home(), Some("https://en.wikipedia.org/".to_string()));
+    
+    let mut maps = config.bangs.get("!maps").unwrap().clone();
+    maps.home_url = Some("https://www.google.com/maps".to_string());
+    assert_eq!(maps.home(), Some("https://www.google.com/maps".to_string()));
+    
+    // Templates whose host is the search term have no home page
+    maps.home_url = None;
+    maps.url = "https://{searchTerms}".to_string();
+    assert_eq!(maps.home(), None);
+}
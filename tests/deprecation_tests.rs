@@ -0,0 +1,168 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+};
+use bang_search::{
+    auth::Identity,
+    config::{validate_config, BangDetails, Config},
+    deprecation::parse_date,
+    resolver::{Action, BangResolver, Matched},
+    state::AppState,
+};
+use common::{base_config, read, send};
+use serde_json::Value;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.bangs.insert("!x".to_string(), BangDetails {
+        url: "https://x.com/search?q={searchTerms}".to_string(),
+        name: "X".to_string(),
+        ..Default::default()
+    });
+    config.bangs.insert("!twitter".to_string(), BangDetails {
+        replaced_by: Some("!x".to_string()),
+        deprecated_until: Some("2999-12-31".to_string()),
+        notice: true,
+        ..Default::default()
+    });
+    config.bangs.insert("!birdsite".to_string(), BangDetails {
+        replaced_by: Some("!x".to_string()),
+        deprecated_until: Some("2000-01-01".to_string()),
+        ..Default::default()
+    });
+    config.private_bangs.insert("!oldwiki".to_string(), BangDetails {
+        url: "https://wiki.example.com/search?q={searchTerms}".to_string(),
+        deprecated: true,
+        ..Default::default()
+    });
+    config
+}
+
+async fn get(state: &AppState, uri: &str, cookie: Option<&str>) -> (StatusCode, HeaderMap, String) {
+    let mut request = Request::get(uri);
+    if let Some(cookie) = cookie {
+        request = request.header("cookie", cookie);
+    }
+    read(send(state, request.body(Body::empty()).unwrap()).await).await
+}
+
+#[test]
+fn test_replaced_and_retired_bangs() {
+    let config = test_config();
+    validate_config(&config).unwrap();
+    let resolver = BangResolver::new(&config);
+
+    // The old key goes through its replacement until the end date
+    let resolution = resolver.resolve("!twitter rust", &Identity::Anonymous);
+    assert_eq!(resolution.matched, Matched::Bang { key: "!x".to_string(), private: false });
+    assert_eq!(resolution.url(), Some("https://x.com/search?q=rust"));
+    assert_eq!(resolution.reason, "public bang !twitter, replaced by !x");
+    let deprecation = resolution.deprecation.unwrap();
+    assert!(!deprecation.retired && deprecation.notice);
+    assert_eq!(deprecation.message(), "!twitter is deprecated and stops working after 2999-12-31; use !x instead");
+
+    let resolution = resolver.resolve("!birdsite rust", &Identity::Anonymous);
+    assert_eq!(
+        resolution.action,
+        Action::Invalid("!birdsite was retired after 2000-01-01; use !x instead".to_string())
+    );
+
+    // Deprecated without a replacement still works as before
+    let resolution = resolver.resolve("!oldwiki roadmap", &Identity::Owner);
+    assert_eq!(resolution.url(), Some("https://wiki.example.com/search?q=roadmap"));
+    assert_eq!(resolution.deprecation.unwrap().message(), "!oldwiki is deprecated");
+    assert!(resolver.resolve("!x rust", &Identity::Anonymous).deprecation.is_none());
+}
+
+#[test]
+fn test_validation() {
+    assert_eq!(parse_date("1970-01-01"), Some(0));
+    assert_eq!(parse_date("2024-03-01"), Some(19783));
+    assert_eq!(parse_date("2024-13-01"), None);
+    assert_eq!(parse_date("2024-02-29"), Some(19782));
+    assert_eq!(parse_date("2027-02-29"), None);
+    assert_eq!(parse_date("2027-02-31"), None);
+    assert_eq!(parse_date("2027-04-31"), None);
+    assert_eq!(parse_date("1900-02-29"), None);
+    assert_eq!(parse_date("2000-02-29"), Some(11016));
+    assert_eq!(parse_date("next year"), None);
+
+    let mut config = test_config();
+    config.bangs.get_mut("!twitter").unwrap().deprecated_until = Some("31/12/2999".to_string());
+    let error = validate_config(&config).unwrap_err().to_string();
+    assert!(error.contains("invalid deprecated_until date '31/12/2999'"), "{}", error);
+
+    // Replacements must exist and not be replaced themselves
+    let mut config = test_config();
+    config.bangs.get_mut("!twitter").unwrap().replaced_by = Some("!birdsite".to_string());
+    let error = validate_config(&config).unwrap_err().to_string();
+    assert!(error.contains("'!twitter' is replaced by '!birdsite'"), "{}", error);
+
+    let mut config = test_config();
+    config.bangs.get_mut("!twitter").unwrap().replaced_by = None;
+    assert!(validate_config(&config).unwrap_err().to_string().contains("invalid URL ''"));
+}
+
+#[tokio::test]
+async fn test_notice_shown_once() {
+    let state = AppState::new(test_config()).unwrap();
+
+    let (status, headers, page) = get(&state, "/search?q=!twitter%20rust", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("!twitter is deprecated and stops working after 2999-12-31; use !x instead"), "{}", page);
+    assert!(page.contains(r#"href="/search?q=%21x%20rust""#), "{}", page);
+    let cookie = headers["set-cookie"].to_str().unwrap();
+    assert!(cookie.starts_with("bang_notices=%21twitter;"), "{}", cookie);
+
+    // With the cookie the old key redirects straight through its replacement
+    let (status, headers, _) = get(&state, "/search?q=!twitter%20rust", Some("bang_notices=%21twitter")).await;
+    assert_eq!(status, StatusCode::SEE_OTHER);
+    assert_eq!(headers["location"], "https://x.com/search?q=rust");
+
+    // Retired keys always get the notice, pointing at the replacement
+    let (status, _, page) = get(&state, "/search?q=!birdsite%20rust", Some("bang_notices=%21birdsite")).await;
+    assert_eq!(status, StatusCode::GONE);
+    assert!(page.contains("Bang retired") && page.contains(r#"href="/search?q=%21x%20rust""#), "{}", page);
+
+    // The live endpoint describes the key as typed and names its replacement
+    let (_, _, body) = get(&state, "/live?q=!twitter%20rust", None).await;
+    let live: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(live["bang"], "!twitter");
+    assert_eq!(live["bang_deprecated"], true);
+    assert_eq!(live["bang_replaced_by"], "!x");
+    assert_eq!(live["deprecation"], "!twitter is deprecated and stops working after 2999-12-31; use !x instead");
+}
+
+#[tokio::test]
+async fn test_use_counts() {
+    let state = AppState::new(test_config()).unwrap();
+    for query in ["!twitter%20a", "!twitter%20b", "!birdsite%20c", "!x%20d"] {
+        get(&state, &format!("/search?q={}", query), Some("bang_notices=%21twitter")).await;
+    }
+
+    let (status, headers, body) = get(&state, "/bangs/deprecated", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["cache-control"], "no-store");
+    let bangs: Value = serde_json::from_str(&body).unwrap();
+    let keys: Vec<_> = bangs.as_array().unwrap().iter().map(|b| b["key"].as_str().unwrap()).collect();
+    assert_eq!(keys, vec!["!birdsite", "!twitter"]);
+    assert_eq!(bangs[0]["retired"], true);
+    assert_eq!(bangs[0]["uses"], 1);
+    assert_eq!(bangs[1]["uses"], 2);
+    assert_eq!(bangs[1]["replaced_by"], "!x");
+    assert!(bangs[1]["last_used"].is_i64());
+
+    // Private deprecated bangs need a login, and the listing flags them all
+    let (_, _, body) = get(&state, "/bangs/deprecated?login=secret_token", None).await;
+    let bangs: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(bangs[1]["key"], "!oldwiki");
+    assert_eq!(bangs[1]["private"], true);
+    assert_eq!(bangs[1]["uses"], 0);
+    assert_eq!(bangs[1]["last_used"], Value::Null);
+
+    let (_, _, body) = get(&state, "/bangs?q=twitter&fields=key,deprecated,replaced_by", None).await;
+    let list: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(list["bangs"][0], serde_json::json!({ "key": "!twitter", "deprecated": true, "replaced_by": "!x" }));
+}
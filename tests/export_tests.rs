@@ -0,0 +1,120 @@
+mod common;
+
+use axum::http::{HeaderMap, StatusCode};
+use bang_search::{
+    config::{BangDetails, BangMode, Config, HttpMethod},
+    export::{export, ExportFormat},
+    import::parse_netscape_html,
+};
+use common::{base_config, read};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.host_url = Some("https://search.example.com".to_string());
+    config.private_bangs.insert("!wiki".to_string(), BangDetails {
+        url: "https://wiki.example.com/search?q={searchTerms}".to_string(),
+        name: "Team Wiki".to_string(),
+        ..Default::default()
+    });
+    config.bangs.insert("!intranet".to_string(), BangDetails {
+        url: "https://intranet.example.com/search.do".to_string(),
+        method: HttpMethod::Post,
+        form: BTreeMap::from([("query".to_string(), "{searchTerms}".to_string())]),
+        ..Default::default()
+    });
+    config.bangs.insert("!go".to_string(), BangDetails {
+        url: "https://www.google.com/search?q={searchTerms}".to_string(),
+        mode: BangMode::Navigate,
+        ..Default::default()
+    });
+    config
+}
+
+async fn get(uri: &str) -> (StatusCode, HeaderMap, String) {
+    read(common::get(test_config(), uri).await).await
+}
+
+#[test]
+fn test_bookmarks_import_back() {
+    let config = test_config();
+    let html = export(&config, ExportFormat::Bookmarks, false);
+
+    // Public GET search bangs only, with browser keywords and %s placeholders
+    let imported = parse_netscape_html(&html);
+    assert_eq!(imported.len(), config.bangs.len() - 2);
+    assert!(!html.contains("intranet") && !html.contains("wiki.example.com"));
+    assert!(!html.contains(r#"SHORTCUTURL="go""#));
+    assert!(html.contains(r#"HREF="https://github.com/search?q=%s" SHORTCUTURL="gh">GitHub</A>"#), "{}", html);
+
+    // Importing the file gives back the same bangs
+    for bang in imported {
+        let key = format!("!{}", bang.keyword);
+        assert_eq!(bang.bang.url, config.bangs[&key].url);
+        assert_eq!(bang.bang.name, config.bangs[&key].name);
+    }
+
+    let with_private = export(&config, ExportFormat::Bookmarks, true);
+    assert!(with_private.contains(r#"SHORTCUTURL="wiki">Team Wiki</A>"#));
+}
+
+#[test]
+fn test_policy_and_launcher_formats() {
+    let config = test_config();
+
+    let chrome: Value = serde_json::from_str(&export(&config, ExportFormat::Chrome, true)).unwrap();
+    let engines = chrome["ManagedSearchEngines"].as_array().unwrap();
+    let wiki = engines.iter().find(|e| e["keyword"] == "wiki").unwrap();
+    assert_eq!(wiki["name"], "Team Wiki");
+    assert_eq!(wiki["search_url"], "https://wiki.example.com/search?q={searchTerms}");
+
+    let raycast: Value = serde_json::from_str(&export(&config, ExportFormat::Raycast, false)).unwrap();
+    let github = raycast.as_array().unwrap().iter().find(|l| l["name"] == "GitHub (!gh)").unwrap();
+    assert_eq!(github["link"], "https://github.com/search?q={argument name=\"query\"}");
+
+    let alfred = export(&config, ExportFormat::Alfred, false);
+    assert!(alfred.contains(
+        "alfred://customsearch/GitHub/gh/utf8/noplus/https%3A%2F%2Fgithub.com%2Fsearch%3Fq%3D%7Bquery%7D"
+    ), "{}", alfred);
+
+    assert_eq!("Chrome".parse(), Ok(ExportFormat::Chrome));
+    assert!("opml".parse::<ExportFormat>().is_err());
+}
+
+#[tokio::test]
+async fn test_export_endpoint() {
+    let (status, headers, body) = get("/export/chrome").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(
+        headers["content-disposition"],
+        "attachment; filename=\"bangs-chrome-policy.json\""
+    );
+    assert!(!body.contains("wiki.example.com"));
+
+    let (_, _, body) = get("/export/bookmarks?login=secret_token").await;
+    assert!(body.contains("wiki.example.com"));
+
+    let (status, _, _) = get("/export/opml").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_per_bang_opensearch() {
+    let (status, headers, xml) = get("/export/opensearch/gh").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/opensearchdescription+xml");
+    assert!(xml.contains("<ShortName>GitHub</ShortName>"), "{}", xml);
+    assert!(xml.contains(r#"template="https://github.com/search?q={searchTerms}""#), "{}", xml);
+    assert!(xml.contains(r#"template="https://search.example.com/export/opensearch/%21gh""#), "{}", xml);
+    assert!(!xml.contains("suggestions"), "{}", xml);
+
+    // Private bangs need a login, and POST bangs have no GET template
+    assert_eq!(get("/export/opensearch/wiki").await.0, StatusCode::NOT_FOUND);
+    let (status, _, xml) = get("/export/opensearch/!wiki?login=secret_token").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.contains("/export/opensearch/%21wiki?login=secret_token"), "{}", xml);
+    assert_eq!(get("/export/opensearch/intranet").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get("/export/opensearch/go").await.0, StatusCode::NOT_FOUND);
+}
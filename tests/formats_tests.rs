@@ -0,0 +1,103 @@
+mod common;
+
+use bang_search::{
+    config::{config_schema, read_config, read_config_as, Config},
+    formats::{convert, ConfigFormat},
+};
+use common::set_auth_token;
+use serde_json::Value;
+use std::process::Command;
+
+// Every setting of a config, with map keys sorted so HashMap order doesn't matter
+fn settings(config: &Config) -> Value {
+    serde_json::to_value(config).unwrap()
+}
+
+#[test]
+fn test_formats_load_identically() {
+    set_auth_token();
+    let dir = tempfile::tempdir().unwrap();
+    let yaml = std::fs::read_to_string("config.yml").unwrap();
+    let expected = settings(&read_config("config.yml").unwrap());
+
+    for (format, name) in [(ConfigFormat::Toml, "config.toml"), (ConfigFormat::Json, "config.json")] {
+        let path = dir.path().join(name);
+        std::fs::write(&path, convert(&yaml, ConfigFormat::Yaml, format).unwrap()).unwrap();
+        let config = read_config(path.to_str().unwrap()).unwrap();
+        assert_eq!(settings(&config), expected, "{}", name);
+
+        // And back again
+        let back = convert(&std::fs::read_to_string(&path).unwrap(), format, ConfigFormat::Yaml).unwrap();
+        let path = dir.path().join(format!("{}.yml", name));
+        std::fs::write(&path, back).unwrap();
+        assert_eq!(settings(&read_config(path.to_str().unwrap()).unwrap()), expected);
+    }
+}
+
+#[test]
+fn test_formats_mix_across_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("bangs.conf"),
+        "include = [\"team.json\"]\nauth_token = \"secret\"\n\n[bangs.\"!g\"]\nurl = \"https://www.google.com/search?q={searchTerms}\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("team.json"),
+        r#"{"private_bangs": {"!wiki": {"url": "https://wiki.example.com/?q={searchTerms}"}}}"#,
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("config.d")).unwrap();
+    std::fs::write(dir.path().join("config.d/docs.toml"), "[bangs.\"!rs\"]\nurl = \"https://docs.rs/?q={searchTerms}\"\n").unwrap();
+
+    // The extension doesn't say, so the format is given explicitly
+    let path = dir.path().join("bangs.conf");
+    let config = read_config_as(path.to_str().unwrap(), Some(ConfigFormat::Toml)).unwrap();
+    assert_eq!(config.auth_token, "secret");
+    assert!(config.bangs.contains_key("!g") && config.bangs.contains_key("!rs"));
+    assert!(config.private_bangs.contains_key("!wiki"));
+
+    // Read as YAML by default, which fails
+    assert!(read_config(path.to_str().unwrap()).is_err());
+    assert_eq!("yml".parse(), Ok(ConfigFormat::Yaml));
+    assert!("ini".parse::<ConfigFormat>().is_err());
+}
+
+#[test]
+fn test_schema_is_up_to_date() {
+    let published: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string("config.schema.json").unwrap()).unwrap();
+    assert_eq!(published, config_schema(), "run `bang_search schema > config.schema.json`");
+
+    let properties = &published["properties"];
+    assert!(properties["bangs"].is_object());
+    assert!(properties["include"].is_object());
+    assert!(published["required"].as_array().is_none_or(|r| r.is_empty()));
+}
+
+#[test]
+fn test_convert_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("config.yml");
+    std::fs::write(&input, "include:\n  - extra.yml\nauth_token: \"${TOKEN:-x}\"\nhost_url: ~\nbangs: {}\n").unwrap();
+
+    let output = dir.path().join("config.toml");
+    let status = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["convert", input.to_str().unwrap(), output.to_str().unwrap()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Includes and references are kept as written; nulls are dropped
+    let toml = std::fs::read_to_string(&output).unwrap();
+    assert!(toml.contains("include = [\"extra.yml\"]"), "{}", toml);
+    assert!(toml.contains("auth_token = \"${TOKEN:-x}\""), "{}", toml);
+    assert!(!toml.contains("host_url"), "{}", toml);
+
+    let printed = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["convert", output.to_str().unwrap(), "--to", "json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&printed.stdout).unwrap();
+    assert_eq!(json["auth_token"], "${TOKEN:-x}");
+}
@@ -0,0 +1,135 @@
+use bang_search::{
+    config::{default_config, HttpMethod},
+    import::{parse_netscape_html, plan_import, read_chrome, read_firefox, ImportSource},
+};
+use rusqlite::Connection;
+use std::path::Path;
+use std::process::Command;
+
+const BOOKMARKS: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<DL><p>
+    <DT><H3>Searches</H3>
+    <DL><p>
+        <DT><A HREF="https://docs.rs/releases/search?query=%s" ADD_DATE="1700000000" SHORTCUTURL="rs">Docs.rs &amp; crates</A>
+        <DT><A HREF="https://www.google.com/search?q=%s" SHORTCUTURL="g">Google</A>
+        <DT><A HREF="https://example.com/">No keyword</A>
+        <DT><A HREF="https://mdn.example.com/search?q=%s" shortcuturl="has space">Bad keyword</A>
+    </DL><p>
+</DL><p>
+"#;
+
+fn firefox_places(path: &Path) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT);
+         CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, title TEXT);
+         CREATE TABLE moz_keywords (id INTEGER PRIMARY KEY, keyword TEXT UNIQUE, place_id INTEGER, post_data TEXT);
+         INSERT INTO moz_places VALUES (1, 'https://www.youtube.com/results?search_query=%s', NULL);
+         INSERT INTO moz_places VALUES (2, 'https://search.example.com/find', NULL);
+         INSERT INTO moz_bookmarks VALUES (1, 1, 1, 'YouTube search');
+         INSERT INTO moz_keywords VALUES (1, 'yt', 1, NULL);
+         INSERT INTO moz_keywords VALUES (2, 'find', 2, 'q=%s&lang=en');",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_firefox_keywords() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("places.sqlite");
+    firefox_places(&path);
+    assert_eq!(ImportSource::detect(&path), Some(ImportSource::Firefox));
+
+    let bangs = read_firefox(&path).unwrap();
+    assert_eq!(bangs.len(), 2);
+
+    let find = &bangs[0];
+    assert_eq!(find.keyword, "find");
+    assert_eq!(find.bang.name, "find");
+    assert_eq!(find.bang.method, HttpMethod::Post);
+    assert_eq!(find.bang.form["q"], "{searchTerms}");
+    assert_eq!(find.bang.form["lang"], "en");
+
+    let yt = &bangs[1];
+    assert_eq!(yt.bang.url, "https://www.youtube.com/results?search_query={searchTerms}");
+    assert_eq!(yt.bang.name, "YouTube search");
+}
+
+#[test]
+fn test_chrome_keywords() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Web Data");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE keywords (id INTEGER PRIMARY KEY, short_name TEXT, keyword TEXT, url TEXT);
+         INSERT INTO keywords VALUES (1, 'Wikipedia', 'wp', 'https://en.wikipedia.org/w/index.php?search={searchTerms}');
+         INSERT INTO keywords VALUES (2, 'Intranet', 'intra', 'https://intranet.example.com/?q=%s');
+         INSERT INTO keywords VALUES (3, 'Google', 'google.com', '{google:baseURL}search?q={searchTerms}');",
+    )
+    .unwrap();
+    drop(conn);
+    assert_eq!(ImportSource::detect(&path), Some(ImportSource::Chrome));
+
+    let plan = plan_import(&default_config(), read_chrome(&path).unwrap());
+    assert_eq!(plan.bangs.keys().collect::<Vec<_>>(), vec!["!intra", "!wp"]);
+    assert_eq!(plan.bangs["!intra"].url, "https://intranet.example.com/?q={searchTerms}");
+    assert_eq!(plan.bangs["!intra"].name, "Intranet");
+
+    // Chrome's own placeholders are not something a bang can use
+    assert_eq!(plan.skipped.len(), 1);
+    assert!(plan.skipped[0].contains("!google.com"), "{:?}", plan.skipped);
+}
+
+#[test]
+fn test_bookmark_html_and_conflicts() {
+    let imported = parse_netscape_html(BOOKMARKS);
+    assert_eq!(imported.len(), 3);
+    assert_eq!(imported[0].bang.name, "Docs.rs & crates");
+    assert_eq!(imported[0].bang.url, "https://docs.rs/releases/search?query={searchTerms}");
+
+    let mut config = default_config();
+    config.bangs.get_mut("!g").unwrap().source = Some("config.yml".to_string());
+    config.bangs.get_mut("!g").unwrap().url = "https://www.google.com/search?hl=en&q={searchTerms}".to_string();
+
+    let plan = plan_import(&config, imported);
+    assert_eq!(plan.bangs.keys().collect::<Vec<_>>(), vec!["!rs"]);
+    assert_eq!(plan.conflicts.len(), 1);
+    assert!(plan.conflicts[0].contains("'!g' is already defined in config.yml"), "{:?}", plan.conflicts);
+    assert!(plan.skipped[0].contains("Invalid bang name '!has space'"), "{:?}", plan.skipped);
+
+    // The existing bang is untouched, and a bang with the same URL is not a conflict
+    let plan = plan_import(&default_config(), parse_netscape_html(BOOKMARKS));
+    assert!(plan.conflicts.is_empty());
+    assert!(plan.skipped.iter().any(|s| s.contains("'!g' already exists with the same URL")));
+}
+
+#[test]
+fn test_import_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let bookmarks = dir.path().join("bookmarks.html");
+    std::fs::write(&bookmarks, BOOKMARKS).unwrap();
+    std::fs::write(
+        dir.path().join("config.yml"),
+        "bangs:\n  \"!g\":\n    url: \"https://www.google.com/search?hl=en&q={searchTerms}\"\n",
+    )
+    .unwrap();
+
+    let fragment = dir.path().join("config.d/imported.toml");
+    std::fs::create_dir(dir.path().join("config.d")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bang_search"))
+        .args(["import", bookmarks.to_str().unwrap(), "--private", "--output", fragment.to_str().unwrap()])
+        .arg("--config")
+        .arg(dir.path().join("config.yml"))
+        .output()
+        .unwrap();
+
+    // The conflict fails the command, but the other bangs are still written
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("'!g' is already defined"), "{}", stderr);
+    assert!(stderr.ends_with("1 imported, 1 conflicts, 1 skipped\n"), "{}", stderr);
+
+    let config = bang_search::config::read_config(dir.path().join("config.yml").to_str().unwrap()).unwrap();
+    assert!(config.private_bangs.contains_key("!rs"));
+    assert_eq!(config.bangs["!g"].url, "https://www.google.com/search?hl=en&q={searchTerms}");
+}
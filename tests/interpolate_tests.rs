@@ -0,0 +1,115 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use bang_search::{
+    config::{read_config, BangMode},
+    interpolate::{interpolate, InterpolationError},
+    state::AppState,
+};
+use common::{read, send};
+use serde_json::Value;
+
+#[test]
+fn test_environment_variables_and_defaults() {
+    std::env::set_var("INTERPOLATE_TEST_HOST", "intranet.example.com");
+    std::env::set_var("INTERPOLATE_TEST_EMPTY", "");
+    let mut secrets = Vec::new();
+
+    let url = interpolate("https://${INTERPOLATE_TEST_HOST}/?q={searchTerms}", &mut secrets).unwrap();
+    assert_eq!(url, "https://intranet.example.com/?q={searchTerms}");
+    assert_eq!(interpolate("${INTERPOLATE_TEST_UNSET:-fallback}", &mut secrets).unwrap(), "fallback");
+    assert_eq!(interpolate("${INTERPOLATE_TEST_EMPTY:-fallback}", &mut secrets).unwrap(), "fallback");
+    assert_eq!(interpolate("${INTERPOLATE_TEST_EMPTY}", &mut secrets).unwrap(), "");
+    assert_eq!(interpolate("cost: $${price}", &mut secrets).unwrap(), "cost: ${price}");
+    assert_eq!(secrets, vec!["intranet.example.com", "fallback", "fallback"]);
+
+    assert_eq!(
+        interpolate("${INTERPOLATE_TEST_UNSET}", &mut secrets),
+        Err(InterpolationError::MissingVariable("INTERPOLATE_TEST_UNSET".to_string()))
+    );
+    assert!(matches!(interpolate("${oops", &mut secrets), Err(InterpolationError::Unterminated(_))));
+}
+
+#[test]
+fn test_secret_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let secret = dir.path().join("auth_token");
+    std::fs::write(&secret, "s3cret\n").unwrap();
+
+    let config_path = dir.path().join("config.yml");
+    let config = format!(
+        "auth_token: \"${{file:{}}}\"\nadmin:\n  token: \"${{file:{}/missing:-}}\"\nbangs: {{}}\n",
+        secret.display(),
+        dir.path().display()
+    );
+    std::fs::write(&config_path, config).unwrap();
+
+    let config = read_config(config_path.to_str().unwrap()).unwrap();
+    assert_eq!(config.auth_token, "s3cret");
+    assert_eq!(config.admin.token, "");
+
+    // A secret file without a default must exist
+    std::fs::write(&config_path, "auth_token: \"${file:/nonexistent/secret}\"\nbangs: {}\n").unwrap();
+    let error = read_config(config_path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("Cannot read secret file '/nonexistent/secret'"), "{}", error);
+}
+
+#[test]
+fn test_errors_do_not_echo_secrets() {
+    std::env::set_var("INTERPOLATE_TEST_MODE", "hunter2");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yml");
+
+    // Required variables name the variable in the error
+    std::fs::write(&path, "auth_token: \"${INTERPOLATE_TEST_REQUIRED}\"\nbangs: {}\n").unwrap();
+    let error = read_config(path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("'INTERPOLATE_TEST_REQUIRED' is not set"), "{}", error);
+
+    // A value that fails to parse is left out of the message
+    let bang = "bangs:\n  \"!g\":\n    url: \"https://example.com/?q={searchTerms}\"\n    mode: \"${INTERPOLATE_TEST_MODE}\"\n";
+    std::fs::write(&path, bang).unwrap();
+    let error = read_config(path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(!error.contains("hunter2"), "{}", error);
+    assert!(error.contains("[redacted]"), "{}", error);
+
+    // A value that parses is kept as filled in when the bang is shown as written
+    std::env::set_var("INTERPOLATE_TEST_NAVIGATE", "navigate");
+    std::fs::write(&path, bang.replace("INTERPOLATE_TEST_MODE", "INTERPOLATE_TEST_NAVIGATE")).unwrap();
+    let config = read_config(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.bangs["!g"].as_written().mode, BangMode::Navigate);
+}
+
+#[tokio::test]
+async fn test_bangs_list_shows_urls_as_written() {
+    std::env::set_var("INTERPOLATE_TEST_INTRANET", "wiki.corp.internal");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yml");
+    let yaml = "bangs:\n  \"!wiki\":\n    url: \"https://${INTERPOLATE_TEST_INTRANET}/search?q={searchTerms}\"\n";
+    std::fs::write(&path, yaml).unwrap();
+
+    let mut config = read_config(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.bangs["!wiki"].url, "https://wiki.corp.internal/search?q={searchTerms}");
+    config.redirect_policy.allow_hosts = vec!["example.com".to_string()];
+
+    let state = AppState::new(config).unwrap();
+    let (status, _, body) = read(send(&state, Request::get("/bangs").body(Body::empty()).unwrap()).await).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let list: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(list["bangs"][0]["url"], "https://${INTERPOLATE_TEST_INTRANET}/search?q={searchTerms}");
+    assert!(!body.contains("corp.internal"));
+
+    // The directory page shows the same
+    let (_, _, page) = read(send(&state, Request::get("/directory").body(Body::empty()).unwrap()).await).await;
+    assert!(page.contains("https://${INTERPOLATE_TEST_INTRANET}/search?q={searchTerms}"), "{}", page);
+    assert!(!page.contains("corp.internal"), "{}", page);
+
+    // So does the page for a search the redirect policy blocks
+    let (status, _, page) = read(send(&state, Request::get("/search?q=!wiki%20vpn").body(Body::empty()).unwrap()).await).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(page.contains("https://${INTERPOLATE_TEST_INTRANET}/search?q=vpn"), "{}", page);
+    assert!(!page.contains("corp.internal"), "{}", page);
+}
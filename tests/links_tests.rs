@@ -0,0 +1,118 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::Response,
+};
+use bang_search::{
+    auth::{user_credential, Identity},
+    config::{BangDetails, LinksConfig, UserConfig},
+    links::{expand_link, parse_link_query, LinkError},
+    state::AppState,
+};
+use common::{base_config, search_uri, send};
+
+fn test_state() -> AppState {
+    let mut config = base_config();
+    config.bangs.insert("!go".to_string(), BangDetails {
+        url: "https://{searchTerms}".to_string(),
+        name: "Go To".to_string(),
+        icon: "google".to_string(),
+        ..Default::default()
+    });
+    config.users.insert("alice".to_string(), UserConfig {
+        id: "a1b2c3".to_string(),
+        revoked: false,
+    });
+    config.users.insert("bob".to_string(), UserConfig {
+        id: "d4e5f6".to_string(),
+        revoked: false,
+    });
+    AppState::new(config).unwrap()
+}
+
+async fn search(state: &AppState, query: &str) -> Response {
+    let uri = format!("{}&login=secret_token", search_uri(query));
+    send(state, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+#[test]
+fn test_parse_and_expand() {
+    let config = LinksConfig::default();
+
+    assert_eq!(parse_link_query(&config, "go/wiki rust lang", None, "go/wiki rust lang"), Some(("wiki", "rust lang")));
+    assert_eq!(parse_link_query(&config, "!go wiki rust", Some("!go"), "wiki rust"), Some(("wiki", "rust")));
+    assert_eq!(parse_link_query(&config, "!g wiki", Some("!g"), "wiki"), None);
+
+    assert_eq!(
+        expand_link("https://example.com/{1}/issues?q={rest}", "bang search engine"),
+        "https://example.com/bang/issues?q=bang%20search%20engine"
+    );
+    assert_eq!(expand_link("https://example.com/{2}", "one"), "https://example.com/");
+}
+
+#[test]
+fn test_owner_tracking() {
+    let state = test_state();
+    let alice = Identity::User("alice".to_string());
+    let bob = Identity::User("bob".to_string());
+
+    state.storage.create_link("alice", "wiki", "https://wiki.example.com/{rest}").unwrap();
+    assert!(matches!(state.storage.create_link("bob", "WIKI", "https://other.example.com"), Err(LinkError::AlreadyExists(_))));
+    assert!(matches!(state.storage.create_link("bob", "bad", "javascript:alert(1)"), Err(LinkError::InvalidUrl(_))));
+
+    // Only the owner or the auth_token holder may change a link
+    assert!(matches!(state.storage.delete_link(&bob, "wiki"), Err(LinkError::Forbidden(_))));
+    let link = state.storage.update_link(&alice, "wiki", Some("docs"), None).unwrap();
+    assert_eq!(link.owner, "alice");
+    state.storage.delete_link(&Identity::Owner, "docs").unwrap();
+    assert!(state.storage.list_links().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_link_resolution_and_precedence() {
+    let state = test_state();
+    state.storage.create_link("owner", "wiki", "https://wiki.example.com/search?q={rest}").unwrap();
+
+    let response = search(&state, "!go wiki bangs").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://wiki.example.com/search?q=bangs");
+
+    let response = search(&state, "go/wiki").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://wiki.example.com/search?q=");
+
+    // Unknown link names fall back to the !go bang
+    let response = search(&state, "!go example.com").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://example.com");
+
+    assert_eq!(state.storage.get_link("wiki").unwrap().unwrap().visits, 2);
+}
+
+#[tokio::test]
+async fn test_links_api() {
+    let state = test_state();
+    let alice = user_credential(&state.config(), "a1b2c3");
+    let bob = user_credential(&state.config(), "d4e5f6");
+    let call = |method: &str, uri: String, body: &str| {
+        let request = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+        request.body(Body::from(body.to_string())).unwrap()
+    };
+
+    // Users create links they own
+    let body = r#"{"name": "pr", "url": "https://github.com/org/repo/pull/{1}"}"#;
+    assert_eq!(send(&state, call("POST", format!("/api/links?login={}", alice), body)).await.status(), StatusCode::CREATED);
+    assert_eq!(state.storage.get_link("pr").unwrap().unwrap().owner, "alice");
+    let list = send(&state, call("GET", format!("/api/links?login={}", bob), "")).await;
+    assert_eq!(list.status(), StatusCode::OK);
+
+    let anonymous = Request::delete("/api/links/pr").body(Body::empty()).unwrap();
+    assert_eq!(send(&state, anonymous).await.status(), StatusCode::UNAUTHORIZED);
+
+    // Other users can't change them; the owner and the auth_token holder can
+    let rename = r#"{"name": "pull"}"#;
+    assert_eq!(send(&state, call("PUT", format!("/api/links/pr?login={}", bob), rename)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(send(&state, call("DELETE", format!("/api/links/pr?login={}", bob), "")).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(send(&state, call("PUT", format!("/api/links/pr?login={}", alice), rename)).await.status(), StatusCode::OK);
+    let delete = call("DELETE", "/api/links/pull?login=secret_token".to_string(), "");
+    assert_eq!(send(&state, delete).await.status(), StatusCode::NO_CONTENT);
+}
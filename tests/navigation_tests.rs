@@ -0,0 +1,95 @@
+mod common;
+
+use axum::http::StatusCode;
+use bang_search::{
+    config::{default_config, BangDetails, BangMode, Config, NavigationConfig},
+    navigation::{looks_like_url, navigation_target, NavigationError},
+};
+use common::{get, search_uri};
+
+fn test_config() -> Config {
+    let mut config = default_config();
+    config.bangs.insert("!go".to_string(), BangDetails {
+        url: "https://{searchTerms}".to_string(),
+        name: "Go To".to_string(),
+        mode: BangMode::Navigate,
+        ..Default::default()
+    });
+    config
+}
+
+#[test]
+fn test_navigation_target() {
+    let config = NavigationConfig::default();
+
+    assert_eq!(navigation_target("example.com/a?b=c", &config).unwrap().as_str(), "https://example.com/a?b=c");
+    assert_eq!(navigation_target("http://example.com", &config).unwrap().as_str(), "http://example.com/");
+    assert_eq!(navigation_target("localhost:8080/x", &config).unwrap().as_str(), "https://localhost:8080/x");
+
+    assert!(navigation_target("javascript:alert(1)", &config).is_err());
+    assert!(navigation_target("data:text/html,<b>hi</b>", &config).is_err());
+    assert_eq!(
+        navigation_target("ftp://example.com", &config),
+        Err(NavigationError::SchemeNotAllowed("ftp".to_string()))
+    );
+}
+
+#[test]
+fn test_host_lists() {
+    let config = NavigationConfig {
+        allow_hosts: vec!["example.com".to_string()],
+        deny_hosts: vec!["evil.example.com".to_string()],
+        ..NavigationConfig::default()
+    };
+
+    assert!(navigation_target("docs.example.com", &config).is_ok());
+    assert!(navigation_target("a.evil.example.com", &config).is_err());
+    assert!(navigation_target("example.org", &config).is_err());
+    assert!(navigation_target("notexample.com", &config).is_err());
+}
+
+#[test]
+fn test_looks_like_url() {
+    assert!(looks_like_url("example.com"));
+    assert!(looks_like_url("github.com/rust-lang/rust"));
+    assert!(looks_like_url("https://example.com/path"));
+    assert!(looks_like_url("192.168.1.1:8080"));
+
+    assert!(!looks_like_url("rust programming"));
+    assert!(!looks_like_url("12345"));
+    assert!(!looks_like_url("3.14"));
+    assert!(!looks_like_url("javascript:alert(1)"));
+
+    // File names are not domains, but a scheme or a known TLD makes a URL
+    assert!(!looks_like_url("node.js"));
+    assert!(!looks_like_url("package.json"));
+    assert!(!looks_like_url("index.ts"));
+    assert!(!looks_like_url("example.invalidtld"));
+    assert!(looks_like_url("https://docs.rs"));
+    assert!(looks_like_url("bbc.co.uk"));
+    assert!(looks_like_url("rust-lang.org"));
+
+    // Country codes that double as file extensions are still domains
+    assert!(looks_like_url("crates.rs"));
+    assert!(looks_like_url("example.pl"));
+}
+
+#[tokio::test]
+async fn test_go_bang_and_auto_navigation() {
+    let response = get(test_config(), &search_uri("!go example.com/a?b=c")).await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://example.com/a?b=c");
+
+    let response = get(test_config(), &search_uri("!go javascript:alert(1)")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = get(test_config(), &search_uri("!go node.js")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Bare domains are searched unless auto_navigate is on
+    let response = get(test_config(), &search_uri("example.com")).await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://www.google.com/search?q=example.com");
+
+    let mut config = test_config();
+    config.navigation.auto_navigate = true;
+    let response = get(config, &search_uri("example.com")).await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://example.com/");
+}
@@ -0,0 +1,66 @@
+use bang_search::{
+    config::OpenSearchConfig,
+    opensearch::{render_description, DocumentUrls},
+};
+
+fn render(opensearch: &OpenSearchConfig) -> String {
+    let urls = DocumentUrls::for_host("https://search.example.com/");
+    render_description(opensearch, &urls).unwrap()
+}
+
+#[test]
+fn test_default_description() {
+    let xml = render(&OpenSearchConfig::default());
+
+    assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(xml.contains("<ShortName>Bang Search</ShortName>"));
+    assert!(xml.contains("<InputEncoding>UTF-8</InputEncoding>"));
+    assert!(xml.contains(r#"template="https://search.example.com/search?q={searchTerms}""#));
+    // Optional elements are left out when not configured
+    assert!(!xml.contains("<Image"));
+    assert!(!xml.contains("<Contact>"));
+}
+
+#[test]
+fn test_self_and_search_form() {
+    let xml = render(&OpenSearchConfig::default());
+
+    assert!(xml.contains(r#"rel="self" template="https://search.example.com/opensearch.xml""#));
+    assert!(xml.contains("<SearchForm>https://search.example.com/</SearchForm>"));
+    assert!(xml.contains("<moz:SearchForm>https://search.example.com/</moz:SearchForm>"));
+    assert!(xml.contains(r#"xmlns:moz="http://www.mozilla.org/2006/browser/search/""#));
+}
+
+#[test]
+fn test_configured_fields() {
+    let opensearch = OpenSearchConfig {
+        short_name: "Team Search".to_string(),
+        long_name: Some("Team Bang Search".to_string()),
+        icon: Some("https://search.example.com/icon.png".to_string()),
+        contact: Some("admin@example.com".to_string()),
+        tags: vec!["bangs".to_string(), "search".to_string()],
+        language: Some("en-nz".to_string()),
+        ..OpenSearchConfig::default()
+    };
+    let xml = render(&opensearch);
+
+    assert!(xml.contains("<ShortName>Team Search</ShortName>"));
+    assert!(xml.contains("<LongName>Team Bang Search</LongName>"));
+    assert!(xml.contains(r#"<Image width="16" height="16" type="image/png">https://search.example.com/icon.png</Image>"#));
+    assert!(xml.contains("<Contact>admin@example.com</Contact>"));
+    assert!(xml.contains("<Tags>bangs search</Tags>"));
+    assert!(xml.contains("<Language>en-nz</Language>"));
+}
+
+#[test]
+fn test_values_are_escaped() {
+    let opensearch = OpenSearchConfig {
+        short_name: "Tom & Jerry <Search>".to_string(),
+        description: "Quotes \" and 'apostrophes'".to_string(),
+        ..OpenSearchConfig::default()
+    };
+    let xml = render(&opensearch);
+
+    assert!(xml.contains("<ShortName>Tom &amp; Jerry &lt;Search&gt;</ShortName>"));
+    assert!(!xml.contains("<Search>"));
+}
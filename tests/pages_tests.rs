@@ -0,0 +1,59 @@
+mod common;
+
+use axum::http::StatusCode;
+use bang_search::{
+    config::{BangDetails, Config},
+    pages::escape_html,
+};
+use common::{base_config, get, read};
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.private_bangs.insert("!private".to_string(), BangDetails {
+        url: "https://private.example.com/search?q={searchTerms}".to_string(),
+        name: "Private <Search>".to_string(),
+        icon: "private".to_string(),
+        ..Default::default()
+    });
+    config
+}
+
+async fn get_page(uri: &str) -> (StatusCode, String) {
+    let (status, _, html) = read(get(test_config(), uri).await).await;
+    (status, html)
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;");
+}
+
+#[tokio::test]
+async fn test_index_page() {
+    let (status, html) = get_page("/").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains(r#"<form action="/search" method="get">"#));
+    assert!(html.contains(r#"href="/opensearch.xml""#));
+    assert!(!html.contains(r#"name="login""#));
+}
+
+#[tokio::test]
+async fn test_directory_lists_public_bangs() {
+    let (status, html) = get_page("/directory").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("<code>!gh</code>"));
+    assert!(html.contains(r#"data-bang="!yt""#));
+    assert!(html.contains(r#"<img src="https://github.com/favicon.ico""#));
+    assert!(!html.contains("!private"));
+}
+
+#[tokio::test]
+async fn test_directory_with_login_shows_private_bangs() {
+    let (_, html) = get_page("/directory?login=secret_token").await;
+
+    assert!(html.contains("<code>!private</code>"));
+    assert!(html.contains("Private &lt;Search&gt;"));
+    assert!(html.contains(r#"<option value="Private">Private</option>"#));
+}
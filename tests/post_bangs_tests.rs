@@ -0,0 +1,89 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+};
+use bang_search::{
+    config::{default_config, BangDetails, Config, HttpMethod},
+    forms::{build_form, content_security_policy, should_auto_submit},
+    state::AppState,
+};
+use common::{read, search_uri, send};
+use std::collections::BTreeMap;
+
+fn post_bang() -> BangDetails {
+    BangDetails {
+        url: "https://intranet.example.com/search.do".to_string(),
+        name: "Intranet".to_string(),
+        method: HttpMethod::Post,
+        form: BTreeMap::from([
+            ("query".to_string(), "{searchTerms}".to_string()),
+            ("scope".to_string(), "all".to_string()),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn test_config() -> Config {
+    let mut config = default_config();
+    config.bangs.insert("!intranet".to_string(), post_bang());
+    config
+}
+
+async fn search(query: &str, sec_fetch_site: Option<&str>) -> (StatusCode, HeaderMap, String) {
+    let mut request = Request::get(search_uri(query));
+    if let Some(site) = sec_fetch_site {
+        request = request.header("sec-fetch-site", site);
+    }
+    let state = AppState::new(test_config()).unwrap();
+    read(send(&state, request.body(Body::empty()).unwrap()).await).await
+}
+
+#[test]
+fn test_build_form() {
+    let form = build_form(&post_bang(), "\"quarterly\" <report>");
+
+    assert_eq!(form.action, "https://intranet.example.com/search.do");
+    assert_eq!(form.fields, vec![
+        ("query".to_string(), "\"quarterly\" <report>".to_string()),
+        ("scope".to_string(), "all".to_string()),
+    ]);
+}
+
+#[test]
+fn test_csrf_defaults() {
+    assert!(should_auto_submit(Some("none")));
+    assert!(should_auto_submit(Some("same-origin")));
+    assert!(!should_auto_submit(Some("cross-site")));
+    assert!(!should_auto_submit(None));
+
+    let csp = content_security_policy("https://intranet.example.com/search.do");
+    assert!(csp.contains("form-action https://intranet.example.com;"));
+    assert!(csp.contains("default-src 'none'"));
+    assert!(csp.contains("frame-ancestors 'none'"));
+}
+
+#[tokio::test]
+async fn test_form_page_from_address_bar() {
+    let (status, headers, html) = search("!intranet \"quarterly\" <report>", Some("none")).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers.get("cache-control").unwrap(), "no-store");
+    assert_eq!(headers.get("referrer-policy").unwrap(), "no-referrer");
+    assert!(headers.get("content-security-policy").is_some());
+
+    assert!(html.contains(r#"<form method="post" action="https://intranet.example.com/search.do">"#));
+    assert!(html.contains(r#"<input type="hidden" name="query" value="&quot;quarterly&quot; &lt;report&gt;">"#));
+    assert!(html.contains(r#"<input type="hidden" name="scope" value="all">"#));
+    assert!(html.contains("<script>document.forms[0].submit();</script>"));
+}
+
+#[tokio::test]
+async fn test_cross_site_request_needs_a_click() {
+    let (status, _, html) = search("!intranet report", Some("cross-site")).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("<button type=\"submit\">"));
+    assert!(!html.contains("<script>"));
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use bang_search::{
+    auth::Identity,
+    config::{BangDetails, BangMode, Config, HttpMethod},
+    policy::PolicyViolation,
+    resolver::{Action, BangResolver, Matched},
+    rules::Rule,
+    storage::Storage,
+};
+use common::base_config;
+use std::collections::BTreeMap;
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.private_bangs.insert("!wiki".to_string(), BangDetails {
+        url: "https://wiki.example.com/search?q={searchTerms}".to_string(),
+        name: "Wiki".to_string(),
+        ..Default::default()
+    });
+    // A private bang with the same key as a public one never wins
+    config.private_bangs.insert("!g".to_string(), BangDetails {
+        url: "https://intranet.example.com/?q={searchTerms}".to_string(),
+        ..Default::default()
+    });
+    config
+}
+
+#[test]
+fn test_public_then_private() {
+    let config = test_config();
+    let resolver = BangResolver::new(&config);
+
+    let resolution = resolver.resolve("!g rust lang", &Identity::Owner);
+    assert_eq!(resolution.matched, Matched::Bang { key: "!g".to_string(), private: false });
+    assert_eq!(resolution.url(), Some("https://www.google.com/search?q=rust%20lang"));
+    assert_eq!(resolution.term, "rust lang");
+    assert_eq!(resolution.reason, "public bang !g");
+
+    let resolution = resolver.resolve("roadmap !wiki", &Identity::Owner);
+    assert_eq!(resolution.matched, Matched::Bang { key: "!wiki".to_string(), private: true });
+    assert_eq!(resolution.url(), Some("https://wiki.example.com/search?q=roadmap"));
+
+    // Anonymous callers don't see private bangs
+    let resolution = resolver.resolve("roadmap !wiki", &Identity::Anonymous);
+    assert_eq!(resolution.matched, Matched::Default);
+    assert_eq!(resolution.reason, "bang '!wiki' not found, using the default engine");
+    assert_eq!(resolution.url(), Some("https://www.google.com/search?q=roadmap%20%21wiki"));
+}
+
+#[test]
+fn test_rules_and_default() {
+    let mut config = test_config();
+    config.rules.push(serde_yaml::from_str::<Rule>(
+        "{name: cve, pattern: '^CVE-\\d{4}-\\d+$', url: 'https://nvd.nist.gov/vuln/detail/{0}'}",
+    ).unwrap());
+    let resolver = BangResolver::new(&config);
+
+    let resolution = resolver.resolve("CVE-2021-44228", &Identity::Anonymous);
+    assert_eq!(resolution.matched, Matched::Rule { name: "cve".to_string() });
+    assert_eq!(resolution.url(), Some("https://nvd.nist.gov/vuln/detail/CVE-2021-44228"));
+    assert_eq!(resolution.reason, "rule 'cve'");
+
+    let resolution = resolver.resolve("plain words", &Identity::Anonymous);
+    assert_eq!(resolution.matched, Matched::Default);
+    assert_eq!(resolution.reason, "no bang or rule matched, using the default engine");
+}
+
+#[test]
+fn test_actions() {
+    let mut config = test_config();
+    config.bangs.insert("!form".to_string(), BangDetails {
+        url: "https://search.example.com/find".to_string(),
+        method: HttpMethod::Post,
+        form: BTreeMap::from([("q".to_string(), "{searchTerms}".to_string())]),
+        ..Default::default()
+    });
+    config.bangs.insert("!go".to_string(), BangDetails {
+        url: "https://{searchTerms}".to_string(),
+        mode: BangMode::Navigate,
+        ..Default::default()
+    });
+    config.bangs.insert("!js".to_string(), BangDetails {
+        url: "javascript:alert('{searchTerms}')".to_string(),
+        ..Default::default()
+    });
+    let resolver = BangResolver::new(&config);
+
+    let resolution = resolver.resolve("!form a b", &Identity::Anonymous);
+    let Action::Post(form) = &resolution.action else { panic!("expected a form post") };
+    assert_eq!(form.fields, vec![("q".to_string(), "a b".to_string())]);
+    assert_eq!(resolution.url(), Some("https://search.example.com/find"));
+
+    let resolution = resolver.resolve("!go ftp://example.com", &Identity::Anonymous);
+    assert!(matches!(resolution.action, Action::Invalid(_)));
+    assert_eq!(resolution.url(), None);
+
+    let resolution = resolver.resolve("!js hi", &Identity::Anonymous);
+    assert_eq!(resolution.action, Action::Blocked {
+        target: "javascript:alert('hi')".to_string(),
+        violation: PolicyViolation::SchemeNotAllowed("javascript".to_string()),
+    });
+    assert_eq!(resolution.url(), None);
+
+    // Bookmark commands are left to the caller, which owns the storage
+    let resolution = resolver.resolve("!ba docs https://docs.rs", &Identity::Owner);
+    assert_eq!(resolution.action, Action::Bookmark);
+    assert_eq!(resolution.term, "docs https://docs.rs");
+}
+
+#[test]
+fn test_links_need_storage() {
+    let config = test_config();
+    let storage = Storage::in_memory().unwrap();
+    storage.create_link("owner", "wiki", "https://wiki.example.com/{rest}").unwrap();
+
+    let resolution = BangResolver::new(&config)
+        .with_storage(&storage)
+        .resolve("go/wiki team page", &Identity::Owner);
+    assert_eq!(resolution.matched, Matched::Link { name: "wiki".to_string() });
+    assert_eq!(resolution.url(), Some("https://wiki.example.com/team%20page"));
+    assert_eq!(storage.get_link("wiki").unwrap().unwrap().visits, 1);
+
+    // Without storage, links are skipped rather than failing
+    let resolution = BangResolver::new(&config).resolve("go/wiki team page", &Identity::Owner);
+    assert_eq!(resolution.matched, Matched::Default);
+}
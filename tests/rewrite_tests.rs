@@ -0,0 +1,74 @@
+mod common;
+
+use bang_search::{
+    config::{default_config, Config},
+    rewrite::{rewrite_query, trace_rewrite, RewriteStep},
+};
+use common::{get, json};
+
+fn steps() -> Vec<RewriteStep> {
+    serde_yaml::from_str(r#"
+- name: "strip-tracking"
+  type: replace
+  pattern: '([?&])utm_[a-z]+=[^&\s]*&?'
+  replacement: "$1"
+- name: "site-github"
+  type: replace
+  pattern: '^(.*?)\s+site:github\.com$'
+  replacement: "!gh $1"
+- type: prefix
+  from: "mdn "
+  to: "!mdn "
+- type: suffix
+  from: " on yt"
+  to: " !yt"
+- type: synonyms
+  words:
+    k8s: "kubernetes"
+"#).unwrap()
+}
+
+fn test_config() -> Config {
+    let mut config = default_config();
+    config.rewrites = steps();
+    config
+}
+
+#[test]
+fn test_rewrite_steps() {
+    let steps = steps();
+
+    assert_eq!(rewrite_query(&steps, "https://example.com/a?utm_source=x&id=1"), "https://example.com/a?id=1");
+    assert_eq!(rewrite_query(&steps, "bang search site:github.com"), "!gh bang search");
+    assert_eq!(rewrite_query(&steps, "MDN flexbox"), "!mdn flexbox");
+    assert_eq!(rewrite_query(&steps, "lofi beats on yt"), "lofi beats !yt");
+    assert_eq!(rewrite_query(&steps, "K8S  ingress"), "kubernetes  ingress");
+    assert_eq!(rewrite_query(&steps, "plain query"), "plain query");
+}
+
+#[test]
+fn test_trace_reports_each_step() {
+    let trace = trace_rewrite(&steps(), "k8s docs site:github.com");
+
+    assert_eq!(trace.len(), 5);
+    assert_eq!(trace[1].step, "site-github");
+    assert_eq!(trace[1].output, "!gh k8s docs");
+    assert!(trace[1].changed);
+    assert_eq!(trace[2].step, "prefix");
+    assert!(!trace[2].changed);
+    assert_eq!(trace[4].output, "!gh kubernetes docs");
+}
+
+#[tokio::test]
+async fn test_search_uses_rewritten_query() {
+    let response = get(test_config(), "/search?q=rust%20site:github.com").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://github.com/search?q=rust");
+}
+
+#[tokio::test]
+async fn test_debug_endpoint() {
+    let debug = json(get(test_config(), "/debug/rewrite?q=k8s%20on%20yt").await).await;
+    assert_eq!(debug["query"], "k8s on yt");
+    assert_eq!(debug["output"], "kubernetes !yt");
+    assert_eq!(debug["steps"].as_array().unwrap().len(), 5);
+}
@@ -6,12 +6,13 @@ use axum::{
 use bang_search::{
     config::default_config,
     routes::create_router,
+    state::AppState,
 };
 use tower::ServiceExt;
 
 async fn make_request(query: Option<&str>) -> Response {
     let config = default_config();
-    let app = create_router().with_state(config);
+    let app = create_router().with_state(AppState::new(config).unwrap());
     
     let uri = match query {
         Some(q) => format!("/search?q={}", urlencoding::encode(q)),
@@ -73,4 +74,15 @@ async fn test_search_without_bang() {
     // Should use Google
     let location = response.headers().get("location").unwrap();
     assert_eq!(location, "https://www.google.com/search?q=rust%20programming");
-} 
\ No newline at end of file
+} 
+
+#[tokio::test]
+async fn test_search_with_bare_bang() {
+    let response = make_request(Some("!gh")).await;
+    
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    
+    // Should go to the site's home page instead of an empty search
+    let location = response.headers().get("location").unwrap();
+    assert_eq!(location, "https://github.com/");
+}
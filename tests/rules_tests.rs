@@ -0,0 +1,89 @@
+mod common;
+
+use bang_search::{
+    config::{default_config, load_config, Config},
+    rules::match_rule,
+};
+use common::{get, json, set_auth_token};
+use serde_json::Value;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+async fn test_config() -> Config {
+    // Rules are normally loaded from YAML, which also compiles the patterns
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(br#"
+bangs:
+  "!g":
+    url: "https://www.google.com/search?q={searchTerms}"
+auth_token: "secret_token"
+rules:
+  - name: "jira"
+    pattern: '^([A-Z][A-Z0-9]+-\d+)$'
+    url: "https://jira.example.com/browse/{1}"
+    private: true
+  - name: "github-issue"
+    pattern: '^(?P<owner>[\w.-]+)/(?P<repo>[\w.-]+)#(?P<number>\d+)$'
+    url: "https://github.com/{owner}/{repo}/issues/{number}"
+  - name: "cve"
+    pattern: '^(?i)(CVE-\d{4}-\d{4,})$'
+    url: "https://nvd.nist.gov/vuln/detail/{1}"
+  - name: "cve-mirror"
+    pattern: '^(?i)(CVE-\d{4}-\d{4,})$'
+    url: "https://cve.example.com/{1}"
+    priority: 5
+"#).unwrap();
+    load_config(file.path().to_str().unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_match_rule() {
+    let config = test_config().await;
+
+    let (rule, url) = match_rule(&config.rules, "rust-lang/rust#1234", false).unwrap();
+    assert_eq!(rule.name, "github-issue");
+    assert_eq!(url, "https://github.com/rust-lang/rust/issues/1234");
+
+    // Private rules need a login, and higher priorities win
+    assert!(match_rule(&config.rules, "PROJ-1234", false).is_none());
+    assert_eq!(match_rule(&config.rules, "PROJ-1234", true).unwrap().1, "https://jira.example.com/browse/PROJ-1234");
+    assert_eq!(match_rule(&config.rules, "cve-2024-1234", false).unwrap().0.name, "cve-mirror");
+
+    assert!(match_rule(&config.rules, "rust programming", true).is_none());
+}
+
+#[tokio::test]
+async fn test_invalid_pattern_fails_to_load() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"bangs: {}\nrules:\n  - name: bad\n    pattern: '(unclosed'\n    url: 'https://example.com'\n").unwrap();
+
+    assert!(load_config(file.path().to_str().unwrap()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_search_and_live_use_rules() {
+    let response = get(test_config().await, "/search?q=PROJ-42&login=secret_token").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://jira.example.com/browse/PROJ-42");
+
+    // Bangs still take precedence over rules
+    let response = get(test_config().await, "/search?q=%21g%20PROJ-42&login=secret_token").await;
+    assert_eq!(response.headers().get("location").unwrap(), "https://www.google.com/search?q=PROJ-42");
+
+    let info = json(get(test_config().await, "/live?q=rust-lang/rust%231").await).await;
+    assert_eq!(info["rule"], "github-issue");
+    assert_eq!(info["bang"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_repository_config_loads() {
+    set_auth_token();
+    let config = load_config("config.yml").await.unwrap();
+    let defaults = default_config();
+
+    assert!(!config.rules.is_empty());
+    assert!(config.bangs.len() > defaults.bangs.len());
+    assert_eq!(
+        match_rule(&config.rules, "RFC 9110", false).unwrap().1,
+        "https://www.rfc-editor.org/rfc/rfc9110"
+    );
+}
@@ -0,0 +1,71 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use bang_search::{
+    config::default_config,
+    routes::create_router,
+    state::AppState,
+    suggest::{SuggestProvider, Suggestions},
+};
+use tower::ServiceExt;
+
+async fn get_suggestions(query: &str) -> Suggestions {
+    let mut config = default_config();
+    config.default_engine.suggest = SuggestProvider::Mock {
+        suggestions: vec![
+            "rust programming".to_string(),
+            "rust book".to_string(),
+            "python".to_string(),
+        ],
+    };
+    let app = create_router().with_state(AppState::new(config).unwrap());
+
+    let uri = format!("/suggest?q={}", urlencoding::encode(query));
+    let response = app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "application/x-suggestions+json");
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_suggest_bang_prefix() {
+    let Suggestions(query, completions, descriptions, _) = get_suggestions("!g").await;
+
+    assert_eq!(query, "!g");
+    // Exact match comes first, other bangs starting with !g follow
+    assert_eq!(completions, vec!["!g ", "!gh "]);
+    assert_eq!(descriptions, vec!["Google", "GitHub"]);
+}
+
+#[tokio::test]
+async fn test_suggest_upstream_terms() {
+    let Suggestions(_, completions, _, _) = get_suggestions("rust").await;
+
+    assert_eq!(completions, vec!["rust programming", "rust book"]);
+}
+
+#[tokio::test]
+async fn test_suggest_keeps_bang_prefix() {
+    let Suggestions(_, completions, _, _) = get_suggestions("!yt rust b").await;
+
+    assert_eq!(completions, vec!["!yt rust book"]);
+}
+
+#[tokio::test]
+async fn test_opensearch_advertises_suggest() {
+    let app = create_router().with_state(AppState::new(default_config()).unwrap());
+    let response = app.oneshot(Request::get("/opensearch.xml").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let xml = String::from_utf8(body.to_vec()).unwrap();
+    assert!(xml.contains(r#"template="http://localhost:3000/suggest?q={searchTerms}""#));
+    assert!(!xml.contains("google.com/complete"));
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use axum::http::StatusCode;
+use bang_search::{
+    auth::{identify, user_credential, Identity},
+    config::{validate_config, BangDetails, Config, UserConfig},
+};
+use common::{base_config, get, read, search_uri};
+
+fn test_config() -> Config {
+    let mut config = base_config();
+    config.private_bangs.insert("!private".to_string(), BangDetails {
+        url: "https://private.example.com/search?q={searchTerms}".to_string(),
+        name: "Private Search".to_string(),
+        icon: "private".to_string(),
+        ..Default::default()
+    });
+    config.users.insert("alice".to_string(), UserConfig {
+        id: "a1b2c3".to_string(),
+        revoked: false,
+    });
+    config.users.insert("bob".to_string(), UserConfig {
+        id: "d4e5f6".to_string(),
+        revoked: true,
+    });
+    config
+}
+
+#[test]
+fn test_identify() {
+    let config = test_config();
+
+    assert_eq!(identify(&config, Some("secret_token")), Identity::Owner);
+    assert_eq!(identify(&config, Some(&user_credential(&config, "a1b2c3"))), Identity::User("alice".to_string()));
+    assert_eq!(identify(&config, None), Identity::Anonymous);
+    assert_eq!(identify(&config, Some("a1b2c3.deadbeef")), Identity::Anonymous);
+
+    // Revoked users can no longer use their credential
+    assert_eq!(identify(&config, Some(&user_credential(&config, "d4e5f6"))), Identity::Anonymous);
+
+    // A user named "owner" would share the auth_token holder's data
+    let mut config = test_config();
+    config.users.insert("owner".to_string(), UserConfig { id: "g7h8i9".to_string(), revoked: false });
+    let error = validate_config(&config).unwrap_err().to_string();
+    assert_eq!(error, "User name 'owner' is reserved for the auth_token holder");
+}
+
+#[tokio::test]
+async fn test_user_document_carries_credential() {
+    let config = test_config();
+    let credential = user_credential(&config, "a1b2c3");
+
+    let (status, _, xml) = read(get(config, "/u/a1b2c3/opensearch.xml").await).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.contains(&format!("/search?q={{searchTerms}}&amp;login={}", credential)));
+    assert!(xml.contains(r#"rel="self" template="http://localhost:3000/u/a1b2c3/opensearch.xml""#));
+    assert!(!xml.contains("secret_token"));
+}
+
+#[tokio::test]
+async fn test_unknown_or_revoked_user_document() {
+    assert_eq!(get(test_config(), "/u/unknown/opensearch.xml").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get(test_config(), "/u/d4e5f6/opensearch.xml").await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_search_with_user_credential() {
+    let config = test_config();
+    let credential = user_credential(&config, "a1b2c3");
+    let uri = format!("{}&login={}", search_uri("!private test"), credential);
+
+    let response = get(config, &uri).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers().get("location").unwrap(), "https://private.example.com/search?q=test");
+}